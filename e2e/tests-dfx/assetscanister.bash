@@ -332,3 +332,22 @@ CHERRIES" "$stdout"
     assert_command dfx canister call --query e2e_project_assets list  '(record{})'
     assert_not_match '"/will-delete-this.txt"'
 }
+
+@test "skips assets matched by .dfxignore" {
+    install_asset assetscanister
+
+    dfx_start
+
+    echo "*.map" >.dfxignore
+    echo "/src/e2e_project_assets/assets/private/" >>.dfxignore
+    touch src/e2e_project_assets/assets/main.js.map
+    mkdir src/e2e_project_assets/assets/private
+    touch src/e2e_project_assets/assets/private/secret.txt
+    touch src/e2e_project_assets/assets/public.txt
+    dfx deploy
+
+    assert_command dfx canister call --query e2e_project_assets list  '(record{})'
+    assert_match '"/public.txt"'
+    assert_not_match '"/main.js.map"'
+    assert_not_match '"/private/secret.txt"'
+}
@@ -65,6 +65,7 @@ teardown() {
     dfx_start
     dfx canister create --all
     assert_command dfx build
+    assert_match "Built 2 canisters \(0 failed\) in"
 }
 
 # TODO: Before Tungsten, we need to update this test for code with inter-canister calls.
@@ -162,3 +163,119 @@ teardown() {
   assert_command ls .dfx/actuallylocal/canisters/e2e_project/
   assert_command ls .dfx/actuallylocal/canisters/e2e_project/e2e_project.wasm
 }
+
+@test "check builds canisters without writing build output" {
+    assert_command dfx check --all
+    assert_match "All canisters checked ok."
+    [[ ! -f .dfx/local/canisters/e2e_project/e2e_project.wasm ]]
+}
+
+@test "check requires a canister name or --all" {
+    assert_command_fail dfx check
+    assert_match "--all"
+}
+
+@test "check reports canisters that fail to build" {
+    echo "actor { public func broken() : async Nat { \"not a nat\" } }" >src/e2e_project/main.mo
+    assert_command_fail dfx check e2e_project
+    assert_match "failed the check"
+}
+
+@test "canister candid prints the interface of a built canister" {
+    dfx_start
+    dfx canister create --all
+    assert_command_fail dfx canister candid e2e_project --no-build
+    assert_match "has not been built"
+    dfx build
+    assert_command dfx canister candid e2e_project --no-build
+    assert_match "greet"
+    assert_command dfx canister candid e2e_project --format pretty
+    assert_match "service"
+}
+
+@test "build failures exit with code 3" {
+    dfx_start
+    dfx canister create --all
+    echo "actor { public func broken() : async Nat { \"not a nat\" } }" >src/e2e_project/main.mo
+    assert_command_fail dfx build e2e_project
+    assert_eq "3" "$status"
+    assert_match "Built 0 canisters \(1 failed\)"
+}
+
+@test "build --stdout writes the wasm module to stdout" {
+    dfx_start
+    dfx canister create --all
+    dfx build e2e_project --stdout >out.wasm
+    cmp out.wasm .dfx/local/canisters/e2e_project/e2e_project.wasm
+}
+
+@test "generate --npm writes the declarations as an npm package" {
+    dfx_start
+    dfx canister create --all
+    dfx build
+    assert_command dfx generate e2e_project --npm npm-package
+    [ -f npm-package/package.json ]
+    [ -f npm-package/e2e_project/e2e_project.did.js ]
+    assert_command cat npm-package/index.js
+    assert_match 'export \* as e2e_project from "./e2e_project/index.js";'
+    assert_command cat npm-package/e2e_project/index.js
+    assert_match "$(dfx canister id e2e_project)"
+}
+
+@test "build embeds the git commit when defaults.build.embed_git is set" {
+    cat <<<"$(jq '.defaults.build.embed_git=true' dfx.json)" >dfx.json
+    git init --quiet
+    git add . && git -c user.name=dfx -c user.email=dfx@localhost commit --quiet -m init
+    dfx_start
+    dfx canister create --all
+    assert_command dfx build e2e_project
+    assert_command grep -a -o '"commit":"[0-9a-f]*","dirty":false' .dfx/local/canisters/e2e_project/e2e_project.wasm
+}
+
+@test "dfx clean removes the build outputs" {
+    dfx_start
+    dfx canister create --all
+    assert_command dfx build
+    [ -f .dfx/local/canisters/e2e_project/e2e_project.wasm ]
+
+    assert_command dfx clean --dry-run
+    assert_match "e2e_project.wasm"
+    [ -f .dfx/local/canisters/e2e_project/e2e_project.wasm ]
+
+    assert_command dfx clean
+    assert_match "e2e_project.wasm"
+    [ ! -e .dfx/local/canisters ]
+    [ -f .dfx/local/canister_ids.json ]
+}
+
+@test "dfx clean must be run in a project" {
+    cd ..
+    assert_command_fail dfx clean
+    assert_match "Cannot find dfx configuration file"
+}
+
+@test "build --output json reports the outputs of each canister" {
+    dfx_start
+    dfx canister create --all
+    dfx build --output json 2>/dev/null >build.json
+
+    assert_command jq -r '.[] | .canister + " " + .status' build.json
+    assert_match "e2e_project built"
+    assert_match "e2e_project_assets built"
+    assert_command jq -r '.[] | select(.canister == "e2e_project") | .wasm' build.json
+    assert_match "canisters/e2e_project/e2e_project.wasm"
+    assert_command jq -r '.[] | select(.canister == "e2e_project") | .timings | keys | join(" ")' build.json
+    assert_match "build_secs postbuild_secs prebuild_secs"
+}
+
+@test "build --release and --debug select the build profile" {
+    dfx_start
+    dfx canister create --all
+    assert_command dfx build
+    assert_match "Building canisters \(debug profile\)"
+
+    assert_command dfx build --release
+    assert_match "Building canisters \(release profile\)"
+
+    assert_command_fail dfx build --release --debug
+}
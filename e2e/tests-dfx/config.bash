@@ -26,3 +26,48 @@ teardown() {
     # We don't allow to change values that are non existent.
     assert_command_fail dfx config non_existent 123
 }
+
+@test "dfx config reads nested canister fields and rejects invalid writes" {
+    assert_command dfx config canisters.e2e_project.main
+    assert_eq '"src/e2e_project/main.mo"'
+
+    assert_command dfx config canisters.e2e_project.main src/e2e_project/app.mo
+    assert_command jq -r '.canisters.e2e_project.main' dfx.json
+    assert_eq "src/e2e_project/app.mo"
+
+    assert_command_fail dfx config canisters.e2e_project.main 42
+    assert_match "/canisters/e2e_project/main must be a string"
+    assert_command jq -r '.canisters.e2e_project.main' dfx.json
+    assert_eq "src/e2e_project/app.mo"
+}
+
+@test "a malformed dfx.json is reported with its path" {
+    echo "{ not json" >dfx.json
+    assert_command_fail dfx build
+    assert_match "Failed to load"
+    assert_match "dfx.json"
+}
+
+@test "a missing dfx.json suggests creating a project" {
+    rm dfx.json
+    assert_command_fail dfx build
+    assert_match "Run 'dfx new' to create a project"
+}
+
+@test "unknown fields in dfx.json are rejected with --strict-config" {
+    cat <<<"$(jq '.canster={}' dfx.json)" >dfx.json
+    assert_command dfx config networks
+    assert_command_fail dfx --strict-config build
+    assert_match "Unknown field\(s\) in '.*dfx.json': canster"
+
+    cat <<<"$(jq '.strict=true' dfx.json)" >dfx.json
+    assert_command_fail dfx build
+    assert_match "canster"
+}
+
+@test "mistakes in canister fields are reported with their location" {
+    cat <<<"$(jq '.canisters.e2e_project.main=42' dfx.json)" >dfx.json
+
+    assert_command_fail dfx build
+    assert_match "/canisters/e2e_project/main must be a string"
+}
@@ -74,3 +74,61 @@ teardown() {
     # and also installed the cache itself
     test -f "$(dfx cache show)"/dfx
 }
+
+@test "dfx cache verify detects corrupted binaries" {
+    [ "$USE_IC_REF" ] && skip "skipped for ic-ref"
+
+    assert_command dfx cache install
+    assert_command dfx cache verify
+    assert_match "is intact"
+
+    chmod u+w "$(dfx cache show)"/moc
+    echo "garbage" >"$(dfx cache show)"/moc
+
+    assert_command_fail dfx cache verify
+    assert_match "do not match their checksums: moc"
+}
+
+@test "dfx cache verify reports versions installed without checksums as unverified" {
+    [ "$USE_IC_REF" ] && skip "skipped for ic-ref"
+
+    assert_command dfx cache install
+    chmod u+w "$(dfx cache show)"
+    rm "$(dfx cache show)"/SHA256SUMS
+
+    assert_command dfx cache verify
+    assert_match "cannot be verified"
+}
+
+@test "DFX_CACHE_ROOT relocates the cache" {
+    [ "$USE_IC_REF" ] && skip "skipped for ic-ref"
+
+    export DFX_CACHE_ROOT="$DFX_E2E_TEMP_DIR/cache-root"
+
+    assert_command dfx cache install
+    assert_command dfx cache show
+    assert_match "^$DFX_CACHE_ROOT/versions/"
+    test -f "$(dfx cache show)"/dfx
+    test ! -e "$DFX_CONFIG_ROOT/.cache/dfinity/versions"
+}
+
+@test "dfx cache list --output json lists the installed versions" {
+    [ "$USE_IC_REF" ] && skip "skipped for ic-ref"
+
+    assert_command dfx cache install
+    assert_command dfx cache list --output json
+    assert_command jq -e 'length == 1' <<<"$output"
+}
+
+@test "dfx info versions reports the dfx and cache versions" {
+    [ "$USE_IC_REF" ] && skip "skipped for ic-ref"
+
+    dfx_version=$(dfx --version | cut -d' ' -f2)
+    assert_command dfx info versions
+    assert_match "dfx: $dfx_version"
+    assert_match "cache: $dfx_version \(not installed\)"
+
+    dfx cache install
+    assert_command dfx info versions
+    assert_match "cache: $dfx_version \(installed\)"
+}
@@ -55,6 +55,12 @@ teardown() {
     assert_match 'Creating the "default" identity.' "$stderr"
 }
 
+@test "identity list: --print0 separates names with NUL bytes" {
+    assert_command dfx identity new alice
+    assert_command bash -c 'dfx identity list --print0 | xargs -0 -n1 echo'
+    assert_match 'alice anonymous default'
+}
+
 ##
 ## dfx identity new
 ##
@@ -82,3 +82,30 @@ teardown() {
     assert_command_fail dfx build --network actuallylocal
     assert_match "Cannot find canister id. Please issue 'dfx canister --network actuallylocal create e2e_project"
 }
+
+@test "default_network is used when no network is given" {
+    dfx_start
+    setup_actuallylocal_network
+    dfx_set_wallet
+    cat <<<"$(jq '.default_network="actuallylocal"' dfx.json)" >dfx.json
+
+    assert_command dfx canister create --all
+    assert_command dfx canister --network actuallylocal id e2e_project
+    assert_match "$(jq -r .e2e_project.actuallylocal <canister_ids.json)"
+
+    assert_command_fail dfx canister --network local id e2e_project
+}
+
+@test "dfx start serves the local network when default_network is ic" {
+    cat <<<"$(jq '.default_network="ic"' dfx.json)" >dfx.json
+    dfx_start
+
+    assert_command dfx canister --network local create --all
+    assert_command dfx canister --network local id e2e_project
+}
+
+@test "default_network must name a configured network" {
+    cat <<<"$(jq '.default_network="nowhere"' dfx.json)" >dfx.json
+    assert_command_fail dfx build
+    assert_match "Field 'default_network' names the network 'nowhere'"
+}
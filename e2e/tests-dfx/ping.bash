@@ -24,6 +24,7 @@ teardown() {
     assert_command dfx ping
 
     assert_match "\"ic_api_version\""
+    assert_match "Round-trip time: [0-9]+ ms"
 }
 
 @test "dfx ping succeeds by specific host:post" {
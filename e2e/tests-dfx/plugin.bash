@@ -0,0 +1,45 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+    standard_setup
+
+    dfx_new
+}
+
+teardown() {
+    standard_teardown
+}
+
+@test "unknown commands run the dfx-<command> plugin of the project" {
+    mkdir bin
+    cat >bin/dfx-hello <<'SCRIPT'
+#!/usr/bin/env bash
+echo "hello $* from $DFX_PROJECT_ROOT on $DFX_NETWORK"
+SCRIPT
+    chmod +x bin/dfx-hello
+
+    assert_command dfx hello world --network ic
+    assert_match "hello world --network ic from $(pwd) on ic"
+}
+
+@test "dfx exits with the exit code of a failed plugin" {
+    mkdir bin
+    cat >bin/dfx-fail <<'SCRIPT'
+#!/usr/bin/env bash
+exit 5
+SCRIPT
+    chmod +x bin/dfx-fail
+
+    assert_command_fail dfx fail
+    assert_eq "5" "$status"
+    assert_match "The command 'dfx-fail' exited with code 5."
+}
+
+@test "unknown commands without a plugin list the built-in commands" {
+    assert_command_fail dfx no-such-command
+    assert_eq "2" "$status"
+    assert_match "Unknown command 'no-such-command'"
+    assert_match "build"
+}
@@ -57,3 +57,16 @@ teardown() {
     dfx canister --network "http://127.0.0.1:$webserver_port" create --all
     [ -d ".dfx/http___127_0_0_1_$webserver_port" ]
 }
+
+@test "info url prints the replica url of a network" {
+  # shellcheck disable=SC2094
+  cat <<<"$(jq '.networks.local.bind="127.0.0.1:8123"' dfx.json)" >dfx.json
+  assert_command dfx info url
+  assert_eq "http://127.0.0.1:8123"
+
+  assert_command dfx info url --network ic
+  assert_eq "https://ic0.app"
+
+  DFX_REPLICA_URL="http://10.0.0.1:8000" assert_command dfx info url
+  assert_eq "http://10.0.0.1:8000"
+}
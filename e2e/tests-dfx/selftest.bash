@@ -0,0 +1,16 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+    standard_setup
+}
+
+teardown() {
+    standard_teardown
+}
+
+@test "selftest builds a canister with the cached toolchain" {
+    assert_command dfx selftest
+    assert_match "PASS"
+}
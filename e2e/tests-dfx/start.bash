@@ -102,3 +102,25 @@ teardown() {
 
     assert_command curl --fail http://localhost:"$(cat .dfx/webserver-port)"/sample-asset.txt?canisterId="$ID"
 }
+
+@test "dfx replica logs prints the output of the background replica" {
+    assert_command_fail dfx replica logs
+    assert_match "No local network replica is running"
+
+    dfx_start
+
+    [ -f .dfx/replica.log ]
+    assert_command dfx replica logs
+}
+
+@test "dfx stop removes a stale pid file" {
+    mkdir -p .dfx
+    sh -c 'printf %s "$$" >.dfx/pid'
+
+    assert_command dfx stop
+    assert_match "The local network replica is not running anymore"
+    [ ! -e .dfx/pid ]
+
+    assert_command dfx stop
+    assert_match "No local network replica found"
+}
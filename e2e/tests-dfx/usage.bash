@@ -26,6 +26,13 @@ teardown() {
     fi
 }
 
+@test "invalid usage exits with code 2" {
+    assert_command_fail dfx --unknown-flag
+    assert_eq "2" "$status"
+    assert_command_fail dfx build --unknown-flag
+    assert_eq "2" "$status"
+}
+
 @test "returns the right error if not in a project" {
 
     assert_command_fail dfx build
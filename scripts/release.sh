@@ -67,7 +67,10 @@ build_release_candidate() {
     test -x "$dfx_rc"
 
     echo "Deleting existing dfx cache to make sure not to use a stale binary."
-    $dfx_rc cache delete
+    # On a machine that never ran this version, there is nothing to delete.
+    if [ -d "$($dfx_rc cache show)" ]; then
+        $dfx_rc cache delete --force
+    fi
 }
 
 wait_for_response() {
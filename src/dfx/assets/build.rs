@@ -1,8 +1,10 @@
+use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
+use openssl::sha::Sha256;
 use std::env;
 use std::fs::File;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -50,6 +52,48 @@ fn add_asset_archive(fn_name: &str, f: &mut File) {
     write_archive_accessor(fn_name, f);
 }
 
+/// Ship the SHA-256 of the files in an archive, as `<sha256>  <file name>` lines, so dfx can
+/// check what it unpacks from the archive against the files it was built with.
+fn add_archive_checksums(fn_name: &str, f: &mut File) {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let tgz_path = Path::new(&out_dir).join(format!("{}.tgz", fn_name));
+    let checksums_path = Path::new(&out_dir).join(format!("{}.SHA256SUMS", fn_name));
+
+    let mut checksums = String::new();
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(tgz_path).unwrap()));
+    for file in archive.entries().unwrap() {
+        let mut file = file.unwrap();
+        if !file.header().entry_type().is_file() {
+            continue;
+        }
+        let path = file.path().unwrap().to_path_buf();
+        let path = path.strip_prefix(".").unwrap_or(&path).to_path_buf();
+        let mut content = vec![];
+        file.read_to_end(&mut content).unwrap();
+        let mut sha256 = Sha256::new();
+        sha256.update(&content);
+        checksums.push_str(&format!(
+            "{}  {}\n",
+            hex::encode(sha256.finish()),
+            path.display()
+        ));
+    }
+    std::fs::write(checksums_path, checksums).unwrap();
+
+    f.write_all(
+        format!(
+            "
+        pub fn {fn_name}_checksums() -> &'static str {{
+            include_str!(\"{fn_name}.SHA256SUMS\")
+        }}
+    ",
+            fn_name = fn_name,
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+}
+
 fn add_assets_from_directory(fn_name: &str, f: &mut File, path: &str) {
     let out_dir = env::var("OUT_DIR").unwrap();
     let tgz_path = Path::new(&out_dir).join(format!("{}.tgz", fn_name));
@@ -109,6 +153,7 @@ fn main() {
     .unwrap();
 
     add_asset_archive("binary_cache", &mut f);
+    add_archive_checksums("binary_cache", &mut f);
     add_asset_archive("assetstorage_canister", &mut f);
     add_asset_archive("wallet_canister", &mut f);
     add_asset_archive("ui_canister", &mut f);
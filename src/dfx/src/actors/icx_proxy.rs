@@ -4,7 +4,9 @@ use crate::actors::shutdown_controller::signals::ShutdownSubscribe;
 use crate::actors::shutdown_controller::ShutdownController;
 use crate::lib::error::{DfxError, DfxResult};
 
-use crate::actors::shutdown::{wait_for_child_or_receiver, ChildOrReceiver};
+use crate::actors::shutdown::{
+    terminate_child, wait_for_child_or_receiver, ChildOrReceiver, CHILD_SHUTDOWN_TIMEOUT,
+};
 use actix::{
     Actor, ActorContext, ActorFuture, Addr, AsyncContext, Context, Handler, Recipient,
     ResponseActFuture, Running, WrapFuture,
@@ -223,9 +225,8 @@ fn icx_proxy_start_thread(
             // We don't restart the icx-proxy if done = true.
             match wait_for_child_or_receiver(&mut child, &receiver) {
                 ChildOrReceiver::Receiver => {
-                    debug!(logger, "Got signal to stop. Killing icx-proxy process...");
-                    let _ = child.kill();
-                    let _ = child.wait();
+                    debug!(logger, "Got signal to stop. Stopping icx-proxy process...");
+                    terminate_child(&mut child, CHILD_SHUTDOWN_TIMEOUT);
                     done = true;
                 }
                 ChildOrReceiver::Child => {
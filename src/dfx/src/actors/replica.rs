@@ -6,7 +6,9 @@ use crate::actors::shutdown_controller::ShutdownController;
 use crate::lib::error::{DfxError, DfxResult};
 use crate::lib::replica_config::ReplicaConfig;
 
-use crate::actors::shutdown::{wait_for_child_or_receiver, ChildOrReceiver};
+use crate::actors::shutdown::{
+    terminate_child, wait_for_child_or_receiver, ChildOrReceiver, CHILD_SHUTDOWN_TIMEOUT,
+};
 use actix::{
     Actor, ActorContext, ActorFuture, Addr, AsyncContext, Context, Handler, Recipient,
     ResponseActFuture, Running, WrapFuture,
@@ -282,9 +284,8 @@ fn replica_start_thread(
             // We don't restart the replica if done = true.
             match wait_for_child_or_receiver(&mut child, &receiver) {
                 ChildOrReceiver::Receiver => {
-                    debug!(logger, "Got signal to stop. Killing replica process...");
-                    let _ = child.kill();
-                    let _ = child.wait();
+                    debug!(logger, "Got signal to stop. Stopping replica process...");
+                    terminate_child(&mut child, CHILD_SHUTDOWN_TIMEOUT);
                     done = true;
                 }
                 ChildOrReceiver::Child => {
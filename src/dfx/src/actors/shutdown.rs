@@ -1,4 +1,9 @@
 use crossbeam::channel::Receiver;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessExt, Signal};
+
+/// How long a child process has to exit after being asked to, before it is killed.
+pub const CHILD_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Differentiate between:
 ///   - the process exited (Child)
@@ -33,3 +38,47 @@ pub fn wait_for_child_or_receiver(
         };
     }
 }
+
+/// Stops a child process. It is sent SIGTERM first so it can shut down cleanly, and killed if
+/// it is still running after `timeout`.
+pub fn terminate_child(child: &mut std::process::Child, timeout: Duration) {
+    Process::new(child.id() as Pid, None, 0).kill(Signal::Term);
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if let Ok(Some(_)) = child.try_wait() {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    #[test]
+    fn child_is_terminated_with_sigterm() {
+        let mut child = Command::new("sleep").arg("60").spawn().unwrap();
+        let start = Instant::now();
+        terminate_child(&mut child, Duration::from_secs(10));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(child.try_wait().unwrap().unwrap().signal(), Some(15));
+    }
+
+    #[test]
+    fn child_ignoring_sigterm_is_killed_after_the_timeout() {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("trap '' TERM; while :; do sleep 0.1; done")
+            .spawn()
+            .unwrap();
+        // Give the shell time to install the trap.
+        std::thread::sleep(Duration::from_millis(200));
+        terminate_child(&mut child, Duration::from_millis(300));
+        assert_eq!(child.try_wait().unwrap().unwrap().signal(), Some(9));
+    }
+}
@@ -1,11 +1,23 @@
-use crate::lib::builders::BuildConfig;
+use crate::config::cache::{self, DiskBasedCache};
+use crate::config::dfinity::Profile;
+use crate::config::dfx_version;
+use crate::lib::builders::{BuildConfig, BuildOutput};
+use crate::lib::canister_info::CanisterInfo;
+use crate::lib::dist;
 use crate::lib::environment::Environment;
-use crate::lib::error::DfxResult;
+use crate::lib::error::{BuildError, DfxError, DfxResult};
 use crate::lib::models::canister::CanisterPool;
 use crate::lib::models::canister_id_store::CanisterIdStore;
 use crate::lib::provider::create_agent_environment;
+use crate::util::{blob_from_arguments, get_candid_init_type};
 
+use anyhow::{anyhow, Context};
 use clap::Clap;
+use ic_types::principal::Principal as CanisterId;
+use semver::Version;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Builds all or specific canisters from the code in your project. By default, all canisters are built.
 #[derive(Clap)]
@@ -22,16 +34,62 @@ pub struct CanisterBuildOpts {
     #[clap(long)]
     check: bool,
 
+    /// Verifies that the init arguments configured in dfx.json (`init_arg` or `init_arg_file`)
+    /// typecheck against the freshly generated interface of each canister.
+    #[clap(long)]
+    check_init: bool,
+
     /// Override the compute network to connect to. By default, the local network is used.
     /// A valid URL (starting with `http:` or `https:`) can be used here, and a special
     /// ephemeral network will be created specifically for this request. E.g.
     /// "http://localhost:12345/" is a valid network name.
     #[clap(long)]
     network: Option<String>,
+
+    /// Writes the built wasm module of the canister to stdout instead of only to the build
+    /// directory, e.g. to pipe it into another tool. Logs are always written to stderr.
+    #[clap(long, requires("canister-name"))]
+    stdout: bool,
+
+    /// Builds with the compilers of another SDK version than the one pinned by the project,
+    /// installing that version first if needed. dfx.json is left unchanged.
+    #[clap(long)]
+    compiler_version: Option<String>,
+
+    /// Builds with the release profile, e.g. with optimizations, whatever the profile
+    /// configured in dfx.json.
+    #[clap(long, conflicts_with("debug"))]
+    release: bool,
+
+    /// Builds with the debug profile, whatever the profile configured in dfx.json.
+    #[clap(long)]
+    debug: bool,
+
+    /// Specifies the format of the output. With "json", the outputs and the status of each
+    /// canister are printed to stdout as a JSON array.
+    #[clap(long, default_value("text"), possible_values(&["text", "json"]), conflicts_with("stdout"))]
+    output: String,
 }
 
 pub fn exec(env: &dyn Environment, opts: CanisterBuildOpts) -> DfxResult {
     let env = create_agent_environment(env, opts.network)?;
+    let env = match &opts.compiler_version {
+        Some(version) => {
+            let version = Version::parse(version)
+                .with_context(|| format!("Invalid compiler version '{}'.", version))?;
+            slog::warn!(
+                env.get_logger(),
+                "Building with the compilers of SDK version {}, which is not the version pinned by the project ({}).",
+                version,
+                env.get_version()
+            );
+            if version != *dfx_version() && !cache::is_version_installed(&version.to_string())? {
+                dist::install_version(&version)?;
+            }
+            env.with_cache(Arc::new(DiskBasedCache::with_version(&version)))
+        }
+        None => env,
+    };
 
     let logger = env.get_logger();
 
@@ -50,6 +108,14 @@ pub fn exec(env: &dyn Environment, opts: CanisterBuildOpts) -> DfxResult {
         .get_config()
         .get_canister_names_with_dependencies(opts.canister_name.as_deref())?;
 
+    if opts.canister_name.is_none() {
+        for canister_name in config.get_config().get_disabled_canister_names()? {
+            if !canister_names.contains(&canister_name) {
+                slog::info!(logger, "Skipping canister '{}': disabled", canister_name);
+            }
+        }
+    }
+
     // Get pool of canisters to build
     let canister_pool = CanisterPool::load(&env, build_mode_check, &canister_names)?;
 
@@ -68,11 +134,167 @@ pub fn exec(env: &dyn Environment, opts: CanisterBuildOpts) -> DfxResult {
         }
     }
 
-    slog::info!(logger, "Building canisters...");
+    let mut build_config =
+        BuildConfig::from_config(&config)?.with_build_mode_check(build_mode_check);
+    if opts.release {
+        build_config = build_config.with_profile(Profile::Release);
+    } else if opts.debug {
+        build_config = build_config.with_profile(Profile::Debug);
+    }
+
+    slog::info!(
+        logger,
+        "Building canisters ({} profile)...",
+        build_config.get_profile()
+    );
+
+    let start = Instant::now();
+    let results = canister_pool.build(build_config)?;
+    let failed = results.iter().filter(|result| result.is_err()).count();
+    slog::info!(
+        logger,
+        "Built {} canisters ({} failed) in {:.1}s",
+        results.len() - failed,
+        failed,
+        start.elapsed().as_secs_f64()
+    );
+    if opts.output == "json" {
+        print_build_report(&canister_pool, &results)?;
+    }
+    for result in results {
+        result.map_err(DfxError::new)?;
+    }
+
+    if opts.check_init {
+        for canister in canister_pool.get_canister_list() {
+            check_init_arg(canister.get_info())?;
+        }
+    }
+
+    if opts.stdout {
+        if let Some(canister_name) = opts.canister_name.as_deref() {
+            let canister = canister_pool
+                .get_first_canister_with_name(canister_name)
+                .ok_or_else(|| anyhow!("Cannot find canister '{}'.", canister_name))?;
+            let wasm_path = canister.get_info().get_build_wasm_path();
+            let wasm = std::fs::read(&wasm_path)
+                .with_context(|| format!("Cannot read '{}'.", wasm_path.display()))?;
+            let mut stdout = std::io::stdout();
+            stdout.write_all(&wasm)?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
 
-    canister_pool.build_or_fail(
-        BuildConfig::from_config(&config)?.with_build_mode_check(build_mode_check),
-    )?;
+/// Print the outputs and the status of each canister of the pool as a JSON array.
+fn print_build_report(
+    canister_pool: &CanisterPool,
+    results: &[Result<&BuildOutput, BuildError>],
+) -> DfxResult {
+    let errors: Vec<(CanisterId, String)> = results
+        .iter()
+        .filter_map(|result| {
+            let err = result.as_ref().err()?;
+            match err {
+                BuildError::PreBuildStepFailed(id, _)
+                | BuildError::BuildStepFailed(id, _)
+                | BuildError::PostBuildStepFailed(id, _) => Some((*id, err.to_string())),
+                _ => None,
+            }
+        })
+        .collect();
 
+    let report: Vec<serde_json::Value> = canister_pool
+        .get_canister_list()
+        .iter()
+        .map(|canister| {
+            let info = canister.get_info();
+            let error = errors
+                .iter()
+                .find(|(id, _)| *id == canister.canister_id())
+                .map(|(_, error)| error);
+            serde_json::json!({
+                "canister": canister.get_name(),
+                "status": if error.is_some() { "failed" } else { "built" },
+                "wasm": info.get_build_wasm_path(),
+                "did": info.get_build_idl_path(),
+                "js": info.get_index_js_path(),
+                "error": error,
+                "timings": canister.get_build_timings().map(|timings| serde_json::json!({
+                    "prebuild_secs": timings.prebuild.as_secs_f64(),
+                    "build_secs": timings.build.as_secs_f64(),
+                    "postbuild_secs": timings.postbuild.as_secs_f64(),
+                })),
+            })
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&report)?);
     Ok(())
 }
+
+/// Verify that the init argument of a canister, if any, matches the init type of its
+/// generated candid interface.
+fn check_init_arg(info: &CanisterInfo) -> DfxResult {
+    if let Some(init_arg) = info.get_init_arg()? {
+        let idl_path = info.get_build_idl_path();
+        let init_type = get_candid_init_type(&idl_path).ok_or_else(|| {
+            anyhow!(
+                "Cannot read the init type of canister '{}' from '{}'.",
+                info.get_name(),
+                idl_path.display()
+            )
+        })?;
+        blob_from_arguments(Some(&init_arg), None, Some("idl"), &Some(init_type)).with_context(
+            || {
+                format!(
+                    "The init argument of canister '{}' does not match its interface.",
+                    info.get_name()
+                )
+            },
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canister_info(root: &std::path::Path, extras: serde_json::Value) -> CanisterInfo {
+        let info = CanisterInfo::for_test("app", root, extras);
+        let idl_path = info.get_build_idl_path();
+        std::fs::create_dir_all(idl_path.parent().unwrap()).unwrap();
+        std::fs::write(&idl_path, "service : (nat, text) -> {}").unwrap();
+        info
+    }
+
+    #[test]
+    fn init_arg_matches_interface() {
+        let root = tempfile::tempdir().unwrap();
+        let info = canister_info(
+            root.path(),
+            serde_json::json!({ "init_arg": "(42, \"hello\")" }),
+        );
+        assert!(check_init_arg(&info).is_ok());
+    }
+
+    #[test]
+    fn init_arg_does_not_match_interface() {
+        let root = tempfile::tempdir().unwrap();
+        let info = canister_info(
+            root.path(),
+            serde_json::json!({ "init_arg": "(\"hello\")" }),
+        );
+        let err = check_init_arg(&info).unwrap_err();
+        assert!(err.to_string().contains("does not match its interface"));
+    }
+
+    #[test]
+    fn absent_init_arg_is_not_checked() {
+        let root = tempfile::tempdir().unwrap();
+        let info = CanisterInfo::for_test("app", root.path(), serde_json::json!({}));
+        assert!(check_init_arg(&info).is_ok());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::config::cache::delete_version;
 use crate::lib::environment::Environment;
-use crate::lib::error::DfxResult;
+use crate::lib::error::{CacheError, DfxError, DfxResult};
 
 use clap::Clap;
 
@@ -8,13 +8,26 @@ use clap::Clap;
 #[derive(Clap)]
 #[clap(name("delete"))]
 pub struct CacheDeleteOpts {
-    #[clap(long)]
+    /// The version to delete. Defaults to the version of dfx being run.
     version: Option<String>,
+
+    #[clap(long("version"), hidden(true), conflicts_with("version"))]
+    version_flag: Option<String>,
+
+    /// Deletes the version even if it is the version of dfx being run.
+    #[clap(long)]
+    force: bool,
 }
 
 pub fn exec(env: &dyn Environment, opts: CacheDeleteOpts) -> DfxResult {
-    match opts.version {
-        Some(v) => delete_version(v.as_str()).map(|_| {}),
-        _ => env.get_cache().delete(),
+    let version = match opts.version.or(opts.version_flag) {
+        Some(v) => v,
+        None => env.get_cache().version_str(),
+    };
+
+    if delete_version(&version, opts.force)? {
+        Ok(())
+    } else {
+        Err(DfxError::new(CacheError::VersionNotInstalled(version)))
     }
 }
@@ -0,0 +1,19 @@
+use crate::config::cache;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+
+use clap::Clap;
+
+/// Installs a single binary into the cache of this dfx version, replacing it if present.
+#[derive(Clap)]
+#[clap(name("fetch"))]
+pub struct CacheFetchOpts {
+    /// Specifies the name of the binary to install, e.g. moc.
+    binary: String,
+}
+
+pub fn exec(env: &dyn Environment, opts: CacheFetchOpts) -> DfxResult {
+    let path = cache::install_binary(&env.get_cache().version_str(), &opts.binary)?;
+    println!("{}", path.display());
+    Ok(())
+}
@@ -1,13 +1,40 @@
+use crate::config::cache::{Cache, DiskBasedCache, InstallObserver};
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 
 use clap::Clap;
+use slog::{debug, Logger};
+use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Forces unpacking the cache from this dfx version.
 #[derive(Clap)]
 #[clap(name("install"))]
 pub struct CacheInstall {}
 
+/// Logs the binaries of a newly installed version.
+struct LogInstallObserver {
+    logger: Logger,
+}
+
+impl InstallObserver for LogInstallObserver {
+    fn installed(&self, version: &str, binaries: &[PathBuf]) {
+        for binary in binaries {
+            debug!(
+                self.logger,
+                "Installed binary of version {}: {}",
+                version,
+                binary.display()
+            );
+        }
+    }
+}
+
 pub fn exec(env: &dyn Environment, _opts: CacheInstall) -> DfxResult {
-    env.get_cache().force_install()
+    let observer = LogInstallObserver {
+        logger: env.get_logger().clone(),
+    };
+    DiskBasedCache::with_version(env.get_version())
+        .with_install_observer(Arc::new(observer))
+        .force_install()
 }
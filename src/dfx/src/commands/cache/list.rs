@@ -5,17 +5,29 @@ use crate::lib::error::DfxResult;
 use clap::Clap;
 use std::io::Write;
 
-/// Lists installed and used version.
+/// Lists installed and used versions, newest first.
 #[derive(Clap)]
 #[clap(name("list"))]
-pub struct CacheListOpts {}
+pub struct CacheListOpts {
+    /// Specifies the format of the output. With "json", the installed versions are printed to
+    /// stdout as a JSON array of strings.
+    #[clap(long, default_value("text"), possible_values(&["text", "json"]))]
+    output: String,
+}
+
+pub fn exec(env: &dyn Environment, opts: CacheListOpts) -> DfxResult {
+    if opts.output == "json" {
+        let versions: Vec<String> = cache::list_versions()?
+            .iter()
+            .map(|version| version.to_string())
+            .collect();
+        println!("{}", serde_json::to_string(&versions)?);
+        return Ok(());
+    }
 
-pub fn exec(env: &dyn Environment, _opts: CacheListOpts) -> DfxResult {
     let mut current_printed = false;
     let current_version = env.get_version();
-    let mut all_versions = cache::list_versions()?;
-    all_versions.sort();
-    for version in all_versions {
+    for version in cache::list_versions()? {
         if current_version == &version {
             current_printed = true;
             // Same version, prefix with `*`.
@@ -4,9 +4,11 @@ use crate::lib::error::DfxResult;
 use clap::Clap;
 
 mod delete;
+mod fetch;
 mod install;
 mod list;
 mod show;
+mod verify;
 
 /// Manages the dfx version cache.
 #[derive(Clap)]
@@ -19,16 +21,20 @@ pub struct CacheOpts {
 #[derive(Clap)]
 pub enum SubCommand {
     Delete(delete::CacheDeleteOpts),
+    Fetch(fetch::CacheFetchOpts),
     Install(install::CacheInstall),
     List(list::CacheListOpts),
     Show(show::CacheShowOpts),
+    Verify(verify::CacheVerifyOpts),
 }
 
 pub fn exec(env: &dyn Environment, opts: CacheOpts) -> DfxResult {
     match opts.subcmd {
         SubCommand::Delete(v) => delete::exec(env, v),
+        SubCommand::Fetch(v) => fetch::exec(env, v),
         SubCommand::Install(v) => install::exec(env, v),
         SubCommand::List(v) => list::exec(env, v),
         SubCommand::Show(v) => show::exec(env, v),
+        SubCommand::Verify(v) => verify::exec(env, v),
     }
 }
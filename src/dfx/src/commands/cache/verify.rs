@@ -0,0 +1,30 @@
+use crate::config::cache;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+
+use clap::Clap;
+
+/// Checks the binaries of a cached version against the checksums recorded when it was installed.
+#[derive(Clap)]
+#[clap(name("verify"))]
+pub struct CacheVerifyOpts {
+    /// The version to verify. Defaults to the version of dfx being run.
+    version: Option<String>,
+}
+
+pub fn exec(env: &dyn Environment, opts: CacheVerifyOpts) -> DfxResult {
+    let version = match opts.version {
+        Some(v) => v,
+        None => env.get_cache().version_str(),
+    };
+
+    if cache::verify_version(&version)? {
+        eprintln!("The cache of version {} is intact.", version);
+    } else {
+        eprintln!(
+            "The cache of version {} was installed without checksums and cannot be verified. Reinstall it with 'dfx cache install' to record them.",
+            version
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::lib::builders::BuildConfig;
+use crate::lib::canister_info::CanisterInfo;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::models::canister::CanisterPool;
+use crate::lib::models::canister_id_store::CanisterIdStore;
+use crate::util::check_candid_file;
+
+use anyhow::{bail, Context};
+use clap::Clap;
+
+/// Prints the candid interface of a canister, building the canister first if needed.
+#[derive(Clap)]
+pub struct CanisterCandidOpts {
+    /// Specifies the name of the canister.
+    canister_name: String,
+
+    /// Fails instead of building the canister if its interface has not been built yet.
+    #[clap(long)]
+    no_build: bool,
+
+    /// Specifies how to print the interface. "raw" prints the generated file as is, "pretty"
+    /// prints it after parsing it with the candid parser.
+    #[clap(long, default_value("raw"), possible_values(&["raw", "pretty"]))]
+    format: String,
+}
+
+pub async fn exec(env: &dyn Environment, opts: CanisterCandidOpts) -> DfxResult {
+    let config = env.get_config_or_anyhow()?;
+    let canister_name = opts.canister_name.as_str();
+    let canister_id = CanisterIdStore::for_env(env)?.find(canister_name);
+    let canister_info = CanisterInfo::load(&config, canister_name, canister_id)?;
+    let idl_path = canister_info.get_build_idl_path();
+
+    if !idl_path.exists() {
+        if opts.no_build {
+            bail!(
+                "The interface of canister '{}' has not been built. Run 'dfx build {}' first.",
+                canister_name,
+                canister_name
+            );
+        }
+        let canister_names = config
+            .get_config()
+            .get_canister_names_with_dependencies(Some(canister_name))?;
+        let canister_pool = CanisterPool::load(env, false, &canister_names)?;
+        canister_pool.build_or_fail(BuildConfig::from_config(&config)?)?;
+    }
+
+    match opts.format.as_str() {
+        "pretty" => {
+            let (env, ty) = check_candid_file(&idl_path)?;
+            println!(
+                "{}",
+                candid::bindings::candid::compile(&env, &ty).trim_end()
+            );
+        }
+        _ => {
+            let content = std::fs::read_to_string(&idl_path)
+                .with_context(|| format!("Cannot read '{}'.", idl_path.display()))?;
+            print!("{}", content);
+        }
+    }
+
+    Ok(())
+}
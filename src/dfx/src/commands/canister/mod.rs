@@ -7,6 +7,7 @@ use clap::Clap;
 use tokio::runtime::Runtime;
 
 mod call;
+mod candid;
 mod create;
 mod delete;
 mod deposit_cycles;
@@ -50,6 +51,7 @@ pub struct CanisterOpts {
 #[derive(Clap)]
 enum SubCommand {
     Call(call::CanisterCallOpts),
+    Candid(candid::CanisterCandidOpts),
     Create(create::CanisterCreateOpts),
     Delete(delete::CanisterDeleteOpts),
     DepositCycles(deposit_cycles::DepositCyclesOpts),
@@ -84,6 +86,7 @@ pub fn exec(env: &dyn Environment, opts: CanisterOpts) -> DfxResult {
         .await?;
         match opts.subcmd {
             SubCommand::Call(v) => call::exec(&agent_env, v, &call_sender).await,
+            SubCommand::Candid(v) => candid::exec(&agent_env, v).await,
             SubCommand::Create(v) => create::exec(&agent_env, v, &call_sender).await,
             SubCommand::Delete(v) => delete::exec(&agent_env, v, &call_sender).await,
             SubCommand::DepositCycles(v) => deposit_cycles::exec(&agent_env, v, &call_sender).await,
@@ -0,0 +1,76 @@
+use crate::lib::builders::BuildConfig;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::models::canister::CanisterPool;
+use crate::lib::provider::create_agent_environment;
+
+use anyhow::bail;
+use clap::Clap;
+
+/// Checks that all or specific canisters build, without writing any build output to the project.
+/// Canisters are built into a temporary directory that is discarded afterward.
+#[derive(Clap)]
+pub struct CheckOpts {
+    /// Specifies the name of the canister to check.
+    /// You must specify either a canister name or the --all option.
+    canister_name: Option<String>,
+
+    /// Checks all canisters configured in the dfx.json file.
+    #[clap(
+        long,
+        conflicts_with("canister-name"),
+        required_unless_present("canister-name")
+    )]
+    all: bool,
+
+    /// Override the compute network to connect to. By default, the local network is used.
+    #[clap(long)]
+    network: Option<String>,
+}
+
+pub fn exec(env: &dyn Environment, opts: CheckOpts) -> DfxResult {
+    let env = create_agent_environment(env, opts.network)?;
+
+    let logger = env.get_logger();
+
+    // Read the config.
+    let config = env.get_config_or_anyhow()?;
+
+    // Check the cache. This will only install the cache if there isn't one installed
+    // already.
+    env.get_cache().install()?;
+
+    let canister_names = config
+        .get_config()
+        .get_canister_names_with_dependencies(opts.canister_name.as_deref())?;
+
+    // Everything dfx writes during the build lands in this directory, which is removed
+    // when it goes out of scope.
+    let temp_dir = tempfile::tempdir()?;
+    let config = config.with_temp_path(temp_dir.path());
+
+    let canister_pool = CanisterPool::load_with_config(&env, &config, true, &canister_names)?;
+
+    slog::info!(logger, "Checking canisters...");
+
+    let build_config = BuildConfig::from_config(&config)?
+        .with_build_mode_check(true)
+        .with_skip_frontend(true);
+    let mut failures = 0;
+    for err in canister_pool
+        .build(build_config)?
+        .into_iter()
+        .filter_map(Result::err)
+    {
+        slog::error!(logger, "{}", err);
+        failures += 1;
+    }
+
+    if failures > 0 {
+        bail!("{} canister(s) failed the check.", failures);
+    }
+
+    slog::info!(logger, "All canisters checked ok.");
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::provider::get_network_descriptor;
+
+use clap::Clap;
+use walkdir::WalkDir;
+
+/// Removes the build outputs of the canisters of the project.
+#[derive(Clap)]
+pub struct CleanOpts {
+    /// Lists the files that would be removed without removing them.
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Override the compute network whose build outputs are removed. By default, the outputs
+    /// of the local network are removed.
+    #[clap(long)]
+    network: Option<String>,
+}
+
+pub fn exec(env: &dyn Environment, opts: CleanOpts) -> DfxResult {
+    let config = env.get_config_or_anyhow()?;
+    let network_descriptor = get_network_descriptor(env, opts.network)?;
+    let build_root = config
+        .get_temp_path()
+        .join(&network_descriptor.name)
+        .join("canisters");
+
+    if !build_root.exists() {
+        eprintln!("Nothing to clean in {}.", build_root.display());
+        return Ok(());
+    }
+
+    for entry in WalkDir::new(&build_root) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            println!("{}", entry.path().display());
+        }
+    }
+
+    if opts.dry_run {
+        eprintln!(
+            "Would remove {}. Run without --dry-run to remove it.",
+            build_root.display()
+        );
+    } else {
+        std::fs::remove_dir_all(&build_root)?;
+        eprintln!("Removed {}.", build_root.display());
+    }
+
+    Ok(())
+}
@@ -52,6 +52,8 @@ pub fn exec(env: &dyn Environment, opts: ConfigOpts) -> DfxResult {
             .get_mut_json()
             .pointer_mut(config_path.as_str())
             .ok_or_else(|| anyhow!("Config path does not exist at '{}'.", config_path))? = value;
+        // Don't write a dfx.json that dfx would refuse to load.
+        config.reload_json()?;
         config.save()
     } else if let Some(value) = config.get_json().pointer(config_path.as_str()) {
         match format {
@@ -5,7 +5,10 @@ use crate::lib::models::canister::CanisterPool;
 use crate::lib::models::canister_id_store::CanisterIdStore;
 use crate::lib::provider::create_agent_environment;
 
+use anyhow::Context;
 use clap::Clap;
+use slog::warn;
+use std::path::{Path, PathBuf};
 
 /// Generate type declarations for canisters from the code in your project
 #[derive(Clap)]
@@ -13,10 +16,20 @@ pub struct GenerateOpts {
     /// Specifies the name of the canister to build.
     /// If you do not specify a canister names, generates types for all canisters.
     canister_name: Option<String>,
+
+    /// Override the compute network whose canister ids are written into the declarations.
+    /// By default, the local network is used.
+    #[clap(long)]
+    network: Option<String>,
+
+    /// Also writes the generated declarations to this directory as an npm package, with a
+    /// package.json and an index.js that re-exports the declarations of each canister.
+    #[clap(long)]
+    npm: Option<PathBuf>,
 }
 
 pub fn exec(env: &dyn Environment, opts: GenerateOpts) -> DfxResult {
-    let env = create_agent_environment(env, None)?;
+    let env = create_agent_environment(env, opts.network)?;
 
     // Read the config.
     let config = env.get_config_or_anyhow()?;
@@ -45,5 +58,194 @@ pub fn exec(env: &dyn Environment, opts: GenerateOpts) -> DfxResult {
         canister.generate(&canister_pool, &build_config)?;
     }
 
+    if let Some(npm_dir) = opts.npm {
+        let project_name = config.get_project_root().file_name().map_or_else(
+            || "project".to_string(),
+            |s| s.to_string_lossy().to_string(),
+        );
+        let mut exports = vec![];
+        for canister in canister_pool.get_canister_list() {
+            let info = canister.get_info();
+            if let Some(declarations_dir) = &info.get_declarations_config().output {
+                let canister_dir = npm_dir.join(info.get_name());
+                let canister_id = store.find(info.get_name()).map(|id| id.to_text());
+                if canister_id.is_none() {
+                    warn!(
+                        env.get_logger(),
+                        "Canister '{}' has not been created, so its id is not written into the npm package.",
+                        info.get_name()
+                    );
+                }
+                copy_declarations(
+                    declarations_dir,
+                    &canister_dir,
+                    info.get_name(),
+                    canister_id.as_deref(),
+                )?;
+                if canister_dir.join("index.js").exists() {
+                    exports.push(info.get_name().to_string());
+                }
+            }
+        }
+        write_npm_package(&npm_dir, &project_name, &exports)?;
+        eprintln!("Wrote npm package to {}", npm_dir.display());
+    }
+
+    Ok(())
+}
+
+/// Copy the generated declarations of a canister into the npm package. The canister id, which
+/// index.js reads from the environment of the bundler, is written into it instead, if it is
+/// known.
+fn copy_declarations(
+    declarations_dir: &Path,
+    canister_dir: &Path,
+    canister_name: &str,
+    canister_id: Option<&str>,
+) -> DfxResult {
+    if !declarations_dir.is_dir() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(canister_dir)
+        .with_context(|| format!("Failed to create dir: {:?}", canister_dir))?;
+    for entry in std::fs::read_dir(declarations_dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let target = canister_dir.join(path.file_name().unwrap());
+        match canister_id {
+            Some(canister_id) if path.file_name() == Some("index.js".as_ref()) => {
+                let content = std::fs::read_to_string(&path)?.replace(
+                    &format!("process.env.{}_CANISTER_ID", canister_name.to_uppercase()),
+                    &format!("\"{}\"", canister_id),
+                );
+                std::fs::write(&target, content)?;
+            }
+            _ => {
+                std::fs::copy(&path, &target)
+                    .with_context(|| format!("Failed to copy {:?} to {:?}", path, target))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write the package.json and the index.js re-exporting the declarations of each canister,
+/// under a JavaScript identifier derived from the canister name.
+fn write_npm_package(npm_dir: &Path, project_name: &str, canister_names: &[String]) -> DfxResult {
+    std::fs::create_dir_all(npm_dir)
+        .with_context(|| format!("Failed to create dir: {:?}", npm_dir))?;
+
+    let package_json = serde_json::json!({
+        "name": format!("{}-declarations", project_name.to_lowercase()),
+        "version": "0.0.0",
+        "private": true,
+        "type": "module",
+        "main": "index.js",
+        "peerDependencies": {
+            "@dfinity/agent": "*"
+        }
+    });
+    std::fs::write(
+        npm_dir.join("package.json"),
+        serde_json::to_string_pretty(&package_json)? + "\n",
+    )?;
+
+    let index_js: String = canister_names
+        .iter()
+        .map(|name| {
+            let identifier: String = name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            format!("export * as {} from \"./{}/index.js\";\n", identifier, name)
+        })
+        .collect();
+    std::fs::write(npm_dir.join("index.js"), index_js)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_declarations(dir: &Path) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            dir.join("index.js"),
+            "export const canisterId = process.env.MY_APP_CANISTER_ID;\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("my_app.did"), "service : {}\n").unwrap();
+    }
+
+    #[test]
+    fn copy_declarations_writes_the_canister_id() {
+        let root = tempfile::tempdir().unwrap();
+        let declarations_dir = root.path().join("declarations");
+        write_declarations(&declarations_dir);
+        let canister_dir = root.path().join("npm/my_app");
+
+        copy_declarations(
+            &declarations_dir,
+            &canister_dir,
+            "my_app",
+            Some("rrkah-fqaaa-aaaaa-aaaaq-cai"),
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(canister_dir.join("index.js")).unwrap(),
+            "export const canisterId = \"rrkah-fqaaa-aaaaa-aaaaq-cai\";\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(canister_dir.join("my_app.did")).unwrap(),
+            "service : {}\n"
+        );
+    }
+
+    #[test]
+    fn copy_declarations_without_canister_id() {
+        let root = tempfile::tempdir().unwrap();
+        let declarations_dir = root.path().join("declarations");
+        write_declarations(&declarations_dir);
+        let canister_dir = root.path().join("npm/my_app");
+
+        copy_declarations(&declarations_dir, &canister_dir, "my_app", None).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(canister_dir.join("index.js")).unwrap(),
+            "export const canisterId = process.env.MY_APP_CANISTER_ID;\n"
+        );
+
+        copy_declarations(
+            &root.path().join("missing"),
+            &root.path().join("npm/other"),
+            "other",
+            None,
+        )
+        .unwrap();
+        assert!(!root.path().join("npm/other").exists());
+    }
+
+    #[test]
+    fn write_npm_package_exports_each_canister() {
+        let root = tempfile::tempdir().unwrap();
+        let npm_dir = root.path().join("npm");
+
+        write_npm_package(
+            &npm_dir,
+            "MyProject",
+            &["my_app".to_string(), "my-assets".to_string()],
+        )
+        .unwrap();
+        let package_json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(npm_dir.join("package.json")).unwrap()).unwrap();
+        assert_eq!(package_json["name"], "myproject-declarations");
+        assert_eq!(package_json["main"], "index.js");
+        assert_eq!(
+            std::fs::read_to_string(npm_dir.join("index.js")).unwrap(),
+            "export * as my_app from \"./my_app/index.js\";\n\
+             export * as my_assets from \"./my-assets/index.js\";\n"
+        );
+    }
+}
@@ -7,14 +7,21 @@ use std::io::Write;
 
 /// Lists existing identities.
 #[derive(Clap)]
-pub struct ListOpts {}
+pub struct ListOpts {
+    /// Separates identity names with a NUL byte instead of a newline, and omits the
+    /// marker for the selected identity. Useful in conjunction with `xargs -0`.
+    #[clap(long)]
+    print0: bool,
+}
 
-pub fn exec(env: &dyn Environment, _opts: ListOpts) -> DfxResult {
+pub fn exec(env: &dyn Environment, opts: ListOpts) -> DfxResult {
     let mgr = IdentityManager::new(env)?;
     let identities = mgr.get_identity_names()?;
     let current_identity = mgr.get_selected_identity_name();
     for identity in identities {
-        if current_identity == &identity {
+        if opts.print0 {
+            print!("{}\0", identity);
+        } else if current_identity == &identity {
             // same identity, suffix with '*'.
             print!("{}", identity);
             std::io::stdout().flush()?;
@@ -25,5 +32,6 @@ pub fn exec(env: &dyn Environment, _opts: ListOpts) -> DfxResult {
             println!("{}", identity);
         }
     }
+    std::io::stdout().flush()?;
     Ok(())
 }
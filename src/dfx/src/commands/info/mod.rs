@@ -0,0 +1,28 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+
+use clap::Clap;
+
+mod url;
+mod versions;
+
+/// Displays information about how dfx is set up.
+#[derive(Clap)]
+#[clap(name("info"))]
+pub struct InfoOpts {
+    #[clap(subcommand)]
+    subcmd: SubCommand,
+}
+
+#[derive(Clap)]
+pub enum SubCommand {
+    Url(url::InfoUrlOpts),
+    Versions(versions::InfoVersionsOpts),
+}
+
+pub fn exec(env: &dyn Environment, opts: InfoOpts) -> DfxResult {
+    match opts.subcmd {
+        SubCommand::Url(v) => url::exec(env, v),
+        SubCommand::Versions(v) => versions::exec(env, v),
+    }
+}
@@ -0,0 +1,29 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::provider::get_network_descriptor;
+
+use anyhow::anyhow;
+use clap::Clap;
+
+/// Prints the URL of the replica that dfx connects to for a network, without connecting to it.
+/// This takes dfx.json, the --network option and the DFX_REPLICA_URL environment variable into
+/// account.
+#[derive(Clap)]
+pub struct InfoUrlOpts {
+    /// Override the compute network to connect to. By default, the local network is used.
+    #[clap(long)]
+    network: Option<String>,
+}
+
+pub fn exec(env: &dyn Environment, opts: InfoUrlOpts) -> DfxResult {
+    let network_descriptor = get_network_descriptor(env, opts.network)?;
+    // The agent always connects to the first provider of the network.
+    let url = network_descriptor.providers.first().ok_or_else(|| {
+        anyhow!(
+            "Cannot find providers for network '{}'.",
+            network_descriptor.name
+        )
+    })?;
+    println!("{}", url);
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use crate::config::dfx_version_str;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+
+use clap::Clap;
+
+/// Prints the version of dfx and the version of the cache it uses for this project, e.g. the
+/// replica and the compilers. The cache version follows DFX_VERSION and the "dfx" field of
+/// dfx.json.
+#[derive(Clap)]
+pub struct InfoVersionsOpts {}
+
+pub fn exec(env: &dyn Environment, _opts: InfoVersionsOpts) -> DfxResult {
+    let cache = env.get_cache();
+    let installed = if cache.is_installed()? {
+        "installed"
+    } else {
+        "not installed"
+    };
+    println!("dfx: {}", dfx_version_str());
+    println!("cache: {} ({})", cache.version_str(), installed);
+    Ok(())
+}
@@ -1,8 +1,8 @@
 use crate::config::dfinity::{ConfigCanistersCanister, ConfigInterface, CONFIG_FILE_NAME};
-use crate::error_invalid_data;
 use crate::lib::environment::Environment;
-use crate::lib::error::DfxResult;
+use crate::lib::error::{DfxError, DfxResult, ProjectError};
 use crate::lib::package_arguments::{self, PackageArguments};
+use crate::{error_invalid_argument, error_invalid_data};
 
 use anyhow::{anyhow, bail};
 use clap::{AppSettings, Clap};
@@ -15,10 +15,14 @@ const CANISTER_ARG: &str = "canister";
 #[derive(Clap)]
 #[clap(setting = AppSettings::Hidden)]
 pub struct LanguageServiceOpts {
-    /// Specifies the canister name. If you don't specify this argument, all canisters are
-    /// processed.
+    /// Specifies the canister name. It can be left out if the project has a single canister.
+    #[clap(conflicts_with("all"))]
     canister: Option<String>,
 
+    /// Not supported: the Motoko language server takes the main file of a single canister.
+    #[clap(long)]
+    all: bool,
+
     /// Forces the language server to start even when run from a terminal.
     #[clap(long)]
     force_tty: bool,
@@ -31,6 +35,8 @@ pub fn exec(env: &dyn Environment, opts: LanguageServiceOpts) -> DfxResult {
     // Are we being run from a terminal? That's most likely not what we want
     if atty::is(atty::Stream::Stdout) && !force_tty {
         Err(anyhow!("The `_language-service` command is meant to be run by editors to start a language service. You probably don't want to run it from a terminal.\nIf you _really_ want to, you can pass the --force-tty flag."))
+    } else if opts.all {
+        Err(all_canisters_error())
     } else if let Some(config) = env.get_config() {
         let main_path = get_main_path(config.get_config(), opts.canister)?;
         let packtool = &config
@@ -41,7 +47,7 @@ pub fn exec(env: &dyn Environment, opts: LanguageServiceOpts) -> DfxResult {
         let package_arguments = package_arguments::load(env.get_cache().as_ref(), packtool)?;
         run_ide(env, main_path, package_arguments)
     } else {
-        Err(anyhow!("Cannot find dfx configuration file in the current working directory. Did you forget to create one?"))
+        Err(DfxError::new(ProjectError::CommandMustBeRunInAProject()))
     }
 }
 
@@ -93,6 +99,16 @@ fn get_main_path(config: &ConfigInterface, canister_name: Option<String>) -> Dfx
         .map(|s| s.to_owned())
 }
 
+/// `mo-ide` keeps only the last `--canister-main` it is given, and several language servers
+/// cannot share the standard input and output of dfx, so there is no way to serve all the
+/// canisters of a project at once.
+fn all_canisters_error() -> DfxError {
+    error_invalid_argument!(
+        "The Motoko language server takes the main file of a single canister, so it cannot be started for all canisters. Please select one using the {0} argument.",
+        CANISTER_ARG
+    )
+}
+
 fn run_ide(
     env: &dyn Environment,
     main_path: String,
@@ -120,3 +136,52 @@ fn run_ide(
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::dfinity::Config;
+    use crate::lib::error::InvalidArgumentError;
+
+    fn config(canisters: &str) -> ConfigInterface {
+        let content = format!(r#"{{ "canisters": {} }}"#, canisters);
+        Config::from_str(&content).unwrap().get_config().clone()
+    }
+
+    #[test]
+    fn single_canister_is_used_by_default() {
+        let config = config(r#"{ "app": { "main": "src/app/main.mo" } }"#);
+        assert_eq!(get_main_path(&config, None).unwrap(), "src/app/main.mo");
+    }
+
+    #[test]
+    fn canister_can_be_named() {
+        let config = config(
+            r#"{
+                "app": { "main": "src/app/main.mo" },
+                "lib": { "main": "src/lib/main.mo" }
+            }"#,
+        );
+        assert_eq!(
+            get_main_path(&config, Some("lib".to_string())).unwrap(),
+            "src/lib/main.mo"
+        );
+        assert!(get_main_path(&config, Some("other".to_string())).is_err());
+
+        let err = get_main_path(&config, None).unwrap_err();
+        assert!(err.to_string().contains("multiple canisters"));
+    }
+
+    #[test]
+    fn all_canisters_are_rejected() {
+        let opts = LanguageServiceOpts::try_parse_from(vec!["_language-service", "--all"]).unwrap();
+        assert!(opts.all);
+        let err = all_canisters_error();
+        assert!(err.is::<InvalidArgumentError>());
+        assert!(err.to_string().contains("single canister"));
+
+        assert!(
+            LanguageServiceOpts::try_parse_from(vec!["_language-service", "--all", "app"]).is_err()
+        );
+    }
+}
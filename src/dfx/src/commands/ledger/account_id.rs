@@ -1,17 +1,77 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
-use crate::lib::nns_types::account_identifier::AccountIdentifier;
+use crate::lib::nns_types::account_identifier::{AccountIdentifier, Subaccount};
 
 use clap::Clap;
+use ic_types::principal::Principal;
 
 /// Prints the selected identity's AccountIdentifier.
 #[derive(Clap)]
-pub struct AccountIdOpts {}
+pub struct AccountIdOpts {
+    /// Principal to print the AccountIdentifier of, instead of the selected identity's.
+    #[clap(long)]
+    principal: Option<String>,
 
-pub async fn exec(env: &dyn Environment, _opts: AccountIdOpts) -> DfxResult {
-    let sender = env
-        .get_selected_identity_principal()
-        .expect("Selected identity not instantiated.");
-    println!("{}", AccountIdentifier::new(sender, None));
+    /// Index of the subaccount to use, e.g. 1 for the first numbered subaccount. By default,
+    /// the default subaccount (all zeros) is used.
+    #[clap(long)]
+    subaccount_index: Option<u64>,
+}
+
+pub async fn exec(env: &dyn Environment, opts: AccountIdOpts) -> DfxResult {
+    let principal = match opts.principal {
+        Some(principal) => Principal::from_text(principal)?,
+        None => env
+            .get_selected_identity_principal()
+            .expect("Selected identity not instantiated."),
+    };
+    let subaccount = opts.subaccount_index.map(subaccount_from_index);
+    if let Some(subaccount) = &subaccount {
+        eprintln!("Subaccount: {}", hex::encode(subaccount.to_vec()));
+    }
+    println!("{}", AccountIdentifier::new(principal, subaccount));
     Ok(())
 }
+
+/// The subaccount numbered `index`: the index in big-endian in the last 8 bytes, and zeros
+/// before. Index 0 is the default subaccount.
+fn subaccount_from_index(index: u64) -> Subaccount {
+    let mut subaccount = [0; 32];
+    subaccount[24..].copy_from_slice(&index.to_be_bytes());
+    Subaccount(subaccount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_identifiers_match_known_vectors() {
+        let anonymous = Principal::from_text("2vxsx-fae").unwrap();
+        assert_eq!(
+            AccountIdentifier::new(anonymous.clone(), None).to_hex(),
+            "1c7a48ba6a562aa9eaa2481a9049cdf0433b9738c992d698c31d8abf89cadc79"
+        );
+        assert_eq!(
+            AccountIdentifier::new(anonymous.clone(), Some(subaccount_from_index(0))),
+            AccountIdentifier::new(anonymous.clone(), None)
+        );
+        assert_eq!(
+            AccountIdentifier::new(anonymous, Some(subaccount_from_index(1))).to_hex(),
+            "b8fab0be4ad596a3739ab93e7316a8647ee72e167709441da49ce9171828629d"
+        );
+
+        let ledger = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        assert_eq!(
+            AccountIdentifier::new(ledger, None).to_hex(),
+            "883eef7c44be51afe4a4420d4df4beff708f3cf2f5de5efcc9f58680bb0f3690"
+        );
+    }
+
+    #[test]
+    fn subaccount_index_is_big_endian() {
+        let subaccount = subaccount_from_index(0x0102);
+        assert_eq!(subaccount.0[30..], [1, 2]);
+        assert!(subaccount.0[..30].iter().all(|byte| *byte == 0));
+    }
+}
@@ -1,14 +1,13 @@
+use crate::commands::ledger::get_ledger_defaults;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::nns_types::account_identifier::AccountIdentifier;
 use crate::lib::nns_types::icpts::ICPTs;
 use crate::lib::nns_types::AccountBalanceArgs;
-use crate::lib::nns_types::LEDGER_CANISTER_ID;
 
 use anyhow::anyhow;
 use candid::{Decode, Encode};
 use clap::Clap;
-use ic_types::principal::Principal;
 use std::str::FromStr;
 
 const ACCOUNT_BALANCE_METHOD: &str = "account_balance_dfx";
@@ -34,7 +33,7 @@ pub async fn exec(env: &dyn Environment, opts: BalanceOpts) -> DfxResult {
     let agent = env
         .get_agent()
         .ok_or_else(|| anyhow!("Cannot get HTTP client from environment."))?;
-    let canister_id = Principal::from_text(LEDGER_CANISTER_ID)?;
+    let canister_id = get_ledger_defaults(env).get_ledger_canister_id()?;
 
     let result = agent
         .query(&canister_id, ACCOUNT_BALANCE_METHOD)
@@ -1,18 +1,18 @@
-use crate::commands::ledger::{get_icpts_from_args, send_and_notify};
+use crate::commands::ledger::{
+    get_fees, get_icpts_from_args, get_ledger_defaults, read_amount_file, send_and_notify,
+};
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
-use crate::lib::nns_types::account_identifier::Subaccount;
-use crate::lib::nns_types::icpts::{ICPTs, TRANSACTION_FEE};
+use crate::lib::nns_types::account_identifier::{AccountIdentifier, Subaccount};
+use crate::lib::nns_types::icpts::ICPTs;
+use crate::lib::nns_types::known_memo::KnownMemo;
 use crate::lib::nns_types::{CyclesResponse, Memo};
 
 use crate::util::clap::validators::{e8s_validator, icpts_amount_validator};
 
-use anyhow::anyhow;
 use clap::Clap;
 use ic_types::principal::Principal;
-use std::str::FromStr;
-
-const MEMO_CREATE_CANISTER: u64 = 1095062083_u64;
+use std::path::PathBuf;
 
 /// Create a canister from ICP
 #[derive(Clap)]
@@ -34,6 +34,11 @@ pub struct CreateCanisterOpts {
     #[clap(long, validator(e8s_validator), conflicts_with("amount"))]
     e8s: Option<String>,
 
+    /// Read the ICP to mint, in the same format as `--amount`, from a file, or from stdin if
+    /// the path is "-". This keeps the amount out of the shell history.
+    #[clap(long, conflicts_with_all(&["amount", "icp", "e8s"]))]
+    amount_file: Option<PathBuf>,
+
     /// Transaction fee, default is 10000 e8s.
     #[clap(long, validator(icpts_amount_validator))]
     fee: Option<String>,
@@ -41,29 +46,47 @@ pub struct CreateCanisterOpts {
     /// Max fee, default is 10000 e8s.
     #[clap(long, validator(icpts_amount_validator))]
     max_fee: Option<String>,
+
+    /// Print the transfer to the cycles minting canister that would create the canister,
+    /// without sending it.
+    #[clap(long)]
+    dry_run: bool,
 }
 
 pub async fn exec(env: &dyn Environment, opts: CreateCanisterOpts) -> DfxResult {
-    let amount = get_icpts_from_args(opts.amount, opts.icp, opts.e8s)?;
+    let amount = match &opts.amount_file {
+        Some(path) => Some(read_amount_file(path)?),
+        None => opts.amount,
+    };
+    let amount = get_icpts_from_args(amount, opts.icp, opts.e8s)?;
 
-    let fee = opts.fee.map_or(Ok(TRANSACTION_FEE), |v| {
-        ICPTs::from_str(&v).map_err(|err| anyhow!(err))
-    })?;
+    let (fee, max_fee) = get_fees(opts.fee, opts.max_fee)?;
 
-    let memo = Memo(MEMO_CREATE_CANISTER);
+    let memo = KnownMemo::CreateCanister.to_memo();
 
-    let to_subaccount = Some(Subaccount::from(&Principal::from_text(opts.controller)?));
+    let controller = Principal::from_text(opts.controller)?;
 
-    let max_fee = opts
-        .max_fee
-        .map_or(Ok(TRANSACTION_FEE), |v| ICPTs::from_str(&v))
-        .map_err(|err| anyhow!(err))?;
+    if opts.dry_run {
+        let cycle_minter_id = get_ledger_defaults(env).get_cmc_canister_id()?;
+        print!(
+            "{}",
+            describe_transfer(memo, amount, fee, max_fee, cycle_minter_id, &controller)
+        );
+        return Ok(());
+    }
 
-    let result = send_and_notify(env, memo, amount, fee, to_subaccount, max_fee).await?;
+    let result = send_and_notify(env, memo, amount, fee, &controller, max_fee).await?;
 
-    match result {
+    match result.response {
         CyclesResponse::CanisterCreated(v) => {
             println!("Canister created with id: {:?}", v.to_text());
+            println!(
+                "Amount minted: {} ({} e8s)",
+                result.amount,
+                result.amount.get_e8s()
+            );
+            println!("Fee charged: {} ({} e8s)", result.fee, result.fee.get_e8s());
+            println!("Block height: {}", result.block_height);
         }
         CyclesResponse::Refunded(msg, maybe_block_height) => {
             match maybe_block_height {
@@ -77,3 +100,76 @@ pub async fn exec(env: &dyn Environment, opts: CreateCanisterOpts) -> DfxResult
     };
     Ok(())
 }
+
+/// Describe the transfer to the subaccount of `controller` at the cycles minting canister
+/// that creates a canister controlled by `controller`.
+fn describe_transfer(
+    memo: Memo,
+    amount: ICPTs,
+    fee: ICPTs,
+    max_fee: ICPTs,
+    cycle_minter_id: Principal,
+    controller: &Principal,
+) -> String {
+    let subaccount = Subaccount::from(controller);
+    let to = AccountIdentifier::new(cycle_minter_id, Some(subaccount));
+    format!(
+        "Memo: {}\nTo: {}\nSubaccount: {}\nAmount: {} ({} e8s)\nFee: {} ({} e8s)\nMax fee: {} ({} e8s)\n",
+        memo.0,
+        to,
+        hex::encode(subaccount.to_vec()),
+        amount,
+        amount.get_e8s(),
+        fee,
+        fee.get_e8s(),
+        max_fee,
+        max_fee.get_e8s()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::nns_types::icpts::TRANSACTION_FEE;
+
+    #[test]
+    fn amount_file_conflicts_with_inline_amounts() {
+        let parse = |args: &[&str]| {
+            CreateCanisterOpts::try_parse_from(
+                ["create-canister", "aaaaa-aa"].iter().chain(args.iter()),
+            )
+        };
+
+        assert!(parse(&["--amount-file", "amount.txt"]).is_ok());
+        assert!(parse(&["--amount-file", "amount.txt", "--amount", "1"]).is_err());
+        assert!(parse(&["--amount-file", "amount.txt", "--icp", "1"]).is_err());
+    }
+
+    #[test]
+    fn dry_run_describes_the_transfer_to_the_controller_subaccount() {
+        let controller = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let cycle_minter_id = Principal::from_text("rkp4c-7iaaa-aaaaa-aaaca-cai").unwrap();
+        let description = describe_transfer(
+            KnownMemo::CreateCanister.to_memo(),
+            ICPTs::new(1, 0).unwrap(),
+            TRANSACTION_FEE,
+            TRANSACTION_FEE,
+            cycle_minter_id,
+            &controller,
+        );
+
+        let subaccount = Subaccount::from(&controller);
+        let to = AccountIdentifier::new(cycle_minter_id, Some(subaccount));
+        assert!(description.contains(&format!(
+            "Memo: {}\n",
+            KnownMemo::CreateCanister.to_memo().0
+        )));
+        assert!(description.contains(&format!("To: {}\n", to)));
+        assert!(description.contains(&format!(
+            "Subaccount: 0a{}{}\n",
+            hex::encode(controller.as_slice()),
+            "00".repeat(32 - 1 - controller.as_slice().len())
+        )));
+        assert!(description.contains("Amount: 1.00000000 ICP (100000000 e8s)\n"));
+    }
+}
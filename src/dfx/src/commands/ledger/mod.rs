@@ -1,25 +1,31 @@
+use crate::config::dfinity::ConfigDefaultsLedger;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::nns_types::account_identifier::{AccountIdentifier, Subaccount};
-use crate::lib::nns_types::icpts::ICPTs;
-use crate::lib::nns_types::{
-    BlockHeight, CyclesResponse, Memo, NotifyCanisterArgs, SendArgs, CYCLE_MINTER_CANISTER_ID,
-    LEDGER_CANISTER_ID,
-};
+use crate::lib::nns_types::icpts::{ICPTs, TRANSACTION_FEE};
+use crate::lib::nns_types::{BlockHeight, CyclesResponse, Memo, NotifyCanisterArgs, SendArgs};
 use crate::lib::provider::create_agent_environment;
 use crate::lib::root_key::fetch_root_key_if_needed;
-use crate::lib::waiter::waiter_with_timeout;
+use crate::lib::waiter::{retry_transient, waiter_with_timeout};
+use crate::util::clap::validators::{icpts_amount_validator, parse_icpts};
 use crate::util::expiry_duration;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail, Context};
 use candid::{Decode, Encode};
 use clap::Clap;
+use garcon::Delay;
+use ic_agent::AgentError;
 use ic_types::principal::Principal;
-use std::str::FromStr;
+use std::future::Future;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
 use tokio::runtime::Runtime;
 
 const SEND_METHOD: &str = "send_dfx";
 const NOTIFY_METHOD: &str = "notify_dfx";
+const NOTIFY_RETRY_PAUSE: Duration = Duration::from_secs(1);
+const NOTIFY_RETRY_TIMEOUT: Duration = Duration::from_secs(30);
 
 mod account_id;
 mod balance;
@@ -77,7 +83,7 @@ fn get_icpts_from_args(
                 let icps = s.parse::<u64>().unwrap();
                 ICPTs::from_icpts(icps).map_err(|err| anyhow!(err))?
             }
-            None => ICPTs::from_e8s(0),
+            None => ICPTs::ZERO,
         };
         let icp_from_e8s = match e8s {
             Some(s) => {
@@ -85,27 +91,108 @@ fn get_icpts_from_args(
                 let e8s = s.parse::<u64>().unwrap();
                 ICPTs::from_e8s(e8s)
             }
-            None => ICPTs::from_e8s(0),
+            None => ICPTs::ZERO,
         };
         let amount = icp + icp_from_e8s;
         Ok(amount.map_err(|err| anyhow!(err))?)
     } else {
-        Ok(ICPTs::from_str(&amount.unwrap())
-            .map_err(|err| anyhow!("Could not add ICPs and e8s: {}", err))?)
+        Ok(parse_icpts(&amount.unwrap())?)
     }
 }
 
+/// The ICP of a send is only converted to cycles once the cycles minting canister is notified
+/// of it, which can be retried on its own with `dfx ledger notify`. The retry targets the same
+/// network, and keeps a max fee other than the default one.
+fn notify_failed_message(
+    block_height: BlockHeight,
+    destination: &Principal,
+    network: &str,
+    max_fee: ICPTs,
+) -> String {
+    let mut command = format!(
+        "dfx ledger --network {} notify {} {}",
+        network, block_height, destination
+    );
+    if max_fee != TRANSACTION_FEE {
+        command.push_str(&format!(" --max-fee {}", max_fee));
+    }
+    format!(
+        "The transfer was sent at block height {}, but notifying the cycles minting canister failed. Retry with '{}'.",
+        block_height, command
+    )
+}
+
+/// Parse the `--fee` and `--max-fee` of a send followed by a notify. Both default to the
+/// transaction fee, and a fee above the max fee is rejected before anything is sent.
+fn get_fees(fee: Option<String>, max_fee: Option<String>) -> DfxResult<(ICPTs, ICPTs)> {
+    let parse = |v: Option<String>| -> DfxResult<ICPTs> {
+        match v {
+            Some(v) => Ok(parse_icpts(&v)?),
+            None => Ok(TRANSACTION_FEE),
+        }
+    };
+    let fee = parse(fee)?;
+    let max_fee = parse(max_fee)?;
+    if fee > max_fee {
+        bail!(
+            "The fee ({}) is higher than the max fee ({}). Lower --fee or raise --max-fee.",
+            fee,
+            max_fee
+        );
+    }
+    Ok((fee, max_fee))
+}
+
+/// Read an ICP amount from a file, or from stdin if `path` is "-". The amount is returned
+/// as written, to be parsed by `get_icpts_from_args` like the value of `--amount`.
+fn read_amount_file(path: &Path) -> DfxResult<String> {
+    if path == Path::new("-") {
+        read_amount(&mut std::io::stdin()).context("Failed to read the amount from stdin.")
+    } else {
+        let mut file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}.", path.display()))?;
+        read_amount(&mut file).with_context(|| format!("Failed to read {}.", path.display()))
+    }
+}
+
+fn read_amount(reader: &mut dyn Read) -> DfxResult<String> {
+    let mut amount = String::new();
+    reader.read_to_string(&mut amount)?;
+    let amount = amount.trim();
+    icpts_amount_validator(amount).map_err(|err| anyhow!(err))?;
+    Ok(amount.to_string())
+}
+
+/// The ledger settings of the project, i.e. which ledger and cycles minting canisters to talk to.
+fn get_ledger_defaults(env: &dyn Environment) -> ConfigDefaultsLedger {
+    env.get_config()
+        .map(|config| config.get_config().get_defaults().get_ledger().clone())
+        .unwrap_or_default()
+}
+
+/// The outcome of a send to the cycles minting canister followed by a notify.
+pub struct LedgerSendResult {
+    /// The amount sent to the cycles minting canister.
+    pub amount: ICPTs,
+    /// The transaction fee charged by the ledger.
+    pub fee: ICPTs,
+    /// The block height at which the send transaction was recorded.
+    pub block_height: BlockHeight,
+    /// The response of the cycles minting canister to the notify.
+    pub response: CyclesResponse,
+}
+
 async fn send_and_notify(
     env: &dyn Environment,
     memo: Memo,
     amount: ICPTs,
     fee: ICPTs,
-    to_subaccount: Option<Subaccount>,
+    destination: &Principal,
     max_fee: ICPTs,
-) -> DfxResult<CyclesResponse> {
-    let ledger_canister_id = Principal::from_text(LEDGER_CANISTER_ID)?;
-
-    let cycle_minter_id = Principal::from_text(CYCLE_MINTER_CANISTER_ID)?;
+) -> DfxResult<LedgerSendResult> {
+    let ledger_defaults = get_ledger_defaults(env);
+    let ledger_canister_id = ledger_defaults.get_ledger_canister_id()?;
+    let cycle_minter_id = ledger_defaults.get_cmc_canister_id()?;
 
     let agent = env
         .get_agent()
@@ -113,6 +200,7 @@ async fn send_and_notify(
 
     fetch_root_key_if_needed(env).await?;
 
+    let to_subaccount = Some(Subaccount::from(destination));
     let to = AccountIdentifier::new(cycle_minter_id, to_subaccount);
 
     let result = agent
@@ -131,18 +219,154 @@ async fn send_and_notify(
     let block_height = Decode!(&result, BlockHeight)?;
     println!("Transfer sent at BlockHeight: {}", block_height);
 
-    let result = agent
-        .update(&ledger_canister_id, NOTIFY_METHOD)
-        .with_arg(Encode!(&NotifyCanisterArgs {
-            block_height,
-            max_fee,
-            from_subaccount: None,
-            to_canister: cycle_minter_id,
-            to_subaccount,
-        })?)
-        .call_and_wait(waiter_with_timeout(expiry_duration()))
-        .await?;
+    let notify_args = Encode!(&NotifyCanisterArgs {
+        block_height,
+        max_fee,
+        from_subaccount: None,
+        to_canister: cycle_minter_id,
+        to_subaccount,
+    })?;
+    let result = notify_with_retries(|| async {
+        agent
+            .update(&ledger_canister_id, NOTIFY_METHOD)
+            .with_arg(notify_args.clone())
+            .call_and_wait(waiter_with_timeout(expiry_duration()))
+            .await
+    })
+    .await
+    .with_context(|| {
+        let network = env
+            .get_network_descriptor()
+            .map_or("local", |network| network.name.as_str());
+        notify_failed_message(block_height, destination, network, max_fee)
+    })?;
 
-    let result = Decode!(&result, CyclesResponse)?;
-    Ok(result)
+    let response = Decode!(&result, CyclesResponse)?;
+    Ok(LedgerSendResult {
+        amount,
+        fee,
+        block_height,
+        response,
+    })
+}
+
+/// Notify the cycles minting canister of a send, retrying on transient errors. The ledger
+/// records the notification of each block height, so a retried notify cannot mint the cycles of
+/// a send twice. The send itself is never retried, as it has no `created_at_time` the ledger
+/// could deduplicate it by.
+async fn notify_with_retries<F, Fut>(mut notify: F) -> Result<Vec<u8>, AgentError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Vec<u8>, AgentError>>,
+{
+    let mut waiter = Delay::builder()
+        .throttle(NOTIFY_RETRY_PAUSE)
+        .timeout(NOTIFY_RETRY_TIMEOUT)
+        .build();
+    retry_transient(&mut waiter, || {
+        let notify = notify();
+        async move { notify.await.map(Some) }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icp_and_e8s_are_added_without_overflow() {
+        let amount = get_icpts_from_args(None, Some("12".to_string()), Some("200".to_string()));
+        assert_eq!(amount.unwrap(), ICPTs::new(12, 200).unwrap());
+
+        let amount = get_icpts_from_args(None, None, Some(u64::MAX.to_string()));
+        assert_eq!(amount.unwrap(), ICPTs::MAX);
+
+        let amount = get_icpts_from_args(None, Some("1".to_string()), Some(u64::MAX.to_string()));
+        assert!(amount.unwrap_err().to_string().contains("overflowed"));
+    }
+
+    #[test]
+    fn failed_notify_explains_how_to_retry() {
+        let destination = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let message = notify_failed_message(42, &destination, "local", TRANSACTION_FEE);
+        assert!(message.contains("sent at block height 42"));
+        assert!(
+            message.contains("'dfx ledger --network local notify 42 ryjl3-tyaaa-aaaaa-aaaba-cai'")
+        );
+
+        let max_fee = ICPTs::from_e8s(20_000);
+        let message = notify_failed_message(42, &destination, "ic", max_fee);
+        assert!(message.contains(&format!(
+            "'dfx ledger --network ic notify 42 ryjl3-tyaaa-aaaaa-aaaba-cai --max-fee {}'",
+            max_fee
+        )));
+    }
+
+    fn notify(responses: Vec<Result<Vec<u8>, AgentError>>) -> (Result<Vec<u8>, AgentError>, usize) {
+        let mut responses = responses.into_iter();
+        let mut attempts = 0;
+        let result = Runtime::new().unwrap().block_on(notify_with_retries(|| {
+            attempts += 1;
+            let response = responses.next().unwrap();
+            async move { response }
+        }));
+        (result, attempts)
+    }
+
+    #[test]
+    fn notify_after_a_successful_send_is_retried_on_transient_errors() {
+        let (result, attempts) = notify(vec![
+            Err(AgentError::TimeoutWaitingForResponse()),
+            Ok(b"cycles".to_vec()),
+        ]);
+        assert_eq!(result.unwrap(), b"cycles");
+        assert_eq!(attempts, 2);
+
+        let (result, attempts) = notify(vec![
+            Err(AgentError::MessageError("rejected".to_string())),
+            Ok(b"cycles".to_vec()),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn fees_default_to_the_transaction_fee() {
+        assert_eq!(
+            get_fees(None, None).unwrap(),
+            (TRANSACTION_FEE, TRANSACTION_FEE)
+        );
+        let (fee, max_fee) = get_fees(None, Some("0.001".to_string())).unwrap();
+        assert_eq!(fee, TRANSACTION_FEE);
+        assert_eq!(max_fee, ICPTs::from_e8s(100_000));
+    }
+
+    #[test]
+    fn fee_above_max_fee_is_rejected() {
+        let err = get_fees(Some("0.001".to_string()), None).unwrap_err();
+        assert!(err.to_string().contains("higher than the max fee"));
+
+        let err = get_fees(None, Some("0".to_string())).unwrap_err();
+        assert!(err.to_string().contains("higher than the max fee"));
+
+        assert!(get_fees(Some("0.001".to_string()), Some("0.001".to_string())).is_ok());
+    }
+
+    #[test]
+    fn amount_is_read_from_a_file_or_a_reader() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "12.000002\n").unwrap();
+        let amount = read_amount_file(file.path()).unwrap();
+        assert_eq!(amount, "12.000002");
+        assert_eq!(
+            get_icpts_from_args(Some(amount), None, None).unwrap(),
+            ICPTs::new(12, 200).unwrap()
+        );
+
+        assert_eq!(read_amount(&mut &b" 1.5 "[..]).unwrap(), "1.5");
+        assert_eq!(read_amount(&mut &b"1,000 ICP\n"[..]).unwrap(), "1,000 ICP");
+        assert!(read_amount(&mut &b"one ICP"[..]).is_err());
+        assert!(read_amount_file(Path::new("/nonexistent/amount.txt")).is_err());
+    }
 }
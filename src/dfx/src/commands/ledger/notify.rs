@@ -1,11 +1,10 @@
+use crate::commands::ledger::get_ledger_defaults;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::nns_types::account_identifier::Subaccount;
-use crate::lib::nns_types::icpts::{ICPTs, TRANSACTION_FEE};
-use crate::lib::nns_types::{
-    CyclesResponse, NotifyCanisterArgs, CYCLE_MINTER_CANISTER_ID, LEDGER_CANISTER_ID,
-};
-use crate::util::clap::validators::{e8s_validator, icpts_amount_validator};
+use crate::lib::nns_types::icpts::TRANSACTION_FEE;
+use crate::lib::nns_types::{CyclesResponse, NotifyCanisterArgs};
+use crate::util::clap::validators::{e8s_validator, icpts_amount_validator, parse_icpts};
 
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::lib::waiter::waiter_with_timeout;
@@ -15,7 +14,6 @@ use anyhow::anyhow;
 use candid::{Decode, Encode};
 use clap::Clap;
 use ic_types::principal::Principal;
-use std::str::FromStr;
 
 const NOTIFY_METHOD: &str = "notify_dfx";
 
@@ -45,11 +43,12 @@ pub async fn exec(env: &dyn Environment, opts: NotifyOpts) -> DfxResult {
 
     let max_fee = opts
         .max_fee
-        .map_or(Ok(TRANSACTION_FEE), |v| ICPTs::from_str(&v))
+        .map_or(Ok(TRANSACTION_FEE), |v| parse_icpts(&v))
         .map_err(|err| anyhow!(err))?;
 
-    let ledger_canister_id = Principal::from_text(LEDGER_CANISTER_ID)?;
-    let cycle_minter_id = Principal::from_text(CYCLE_MINTER_CANISTER_ID)?;
+    let ledger_defaults = get_ledger_defaults(env);
+    let ledger_canister_id = ledger_defaults.get_ledger_canister_id()?;
+    let cycle_minter_id = ledger_defaults.get_cmc_canister_id()?;
 
     let to_subaccount = Some(Subaccount::from(&Principal::from_text(
         opts.destination_principal,
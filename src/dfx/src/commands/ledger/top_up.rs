@@ -1,18 +1,13 @@
-use crate::commands::ledger::{get_icpts_from_args, send_and_notify};
+use crate::commands::ledger::{get_fees, get_icpts_from_args, send_and_notify};
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
-use crate::lib::nns_types::account_identifier::Subaccount;
-use crate::lib::nns_types::icpts::{ICPTs, TRANSACTION_FEE};
-use crate::lib::nns_types::{CyclesResponse, Memo};
+use crate::lib::nns_types::known_memo::KnownMemo;
+use crate::lib::nns_types::CyclesResponse;
 
 use crate::util::clap::validators::{e8s_validator, icpts_amount_validator};
 
-use anyhow::anyhow;
 use clap::Clap;
 use ic_types::principal::Principal;
-use std::str::FromStr;
-
-const MEMO_TOP_UP_CANISTER: u64 = 1347768404_u64;
 
 /// Top up a canister with cycles minted from ICP
 #[derive(Clap)]
@@ -46,22 +41,15 @@ pub struct TopUpOpts {
 pub async fn exec(env: &dyn Environment, opts: TopUpOpts) -> DfxResult {
     let amount = get_icpts_from_args(opts.amount, opts.icp, opts.e8s)?;
 
-    let fee = opts.fee.map_or(Ok(TRANSACTION_FEE), |v| {
-        ICPTs::from_str(&v).map_err(|err| anyhow!(err))
-    })?;
-
-    let memo = Memo(MEMO_TOP_UP_CANISTER);
+    let (fee, max_fee) = get_fees(opts.fee, opts.max_fee)?;
 
-    let to_subaccount = Some(Subaccount::from(&Principal::from_text(opts.canister)?));
+    let memo = KnownMemo::TopUp.to_memo();
 
-    let max_fee = opts
-        .max_fee
-        .map_or(Ok(TRANSACTION_FEE), |v| ICPTs::from_str(&v))
-        .map_err(|err| anyhow!(err))?;
+    let canister = Principal::from_text(opts.canister)?;
 
-    let result = send_and_notify(env, memo, amount, fee, to_subaccount, max_fee).await?;
+    let result = send_and_notify(env, memo, amount, fee, &canister, max_fee).await?;
 
-    match result {
+    match result.response {
         CyclesResponse::ToppedUp(()) => {
             println!("Canister was topped up!");
         }
@@ -1,18 +1,19 @@
-use crate::commands::ledger::get_icpts_from_args;
+use crate::commands::ledger::{get_icpts_from_args, get_ledger_defaults};
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::nns_types::account_identifier::AccountIdentifier;
-use crate::lib::nns_types::icpts::{ICPTs, TRANSACTION_FEE};
-use crate::lib::nns_types::{BlockHeight, Memo, SendArgs, LEDGER_CANISTER_ID};
+use crate::lib::nns_types::icpts::TRANSACTION_FEE;
+use crate::lib::nns_types::{BlockHeight, Memo, SendArgs};
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::lib::waiter::waiter_with_timeout;
-use crate::util::clap::validators::{e8s_validator, icpts_amount_validator, memo_validator};
+use crate::util::clap::validators::{
+    e8s_validator, icpts_amount_validator, memo_validator, parse_icpts,
+};
 use crate::util::expiry_duration;
 
 use anyhow::anyhow;
 use candid::{Decode, Encode};
 use clap::Clap;
-use ic_types::principal::Principal;
 use std::str::FromStr;
 
 const SEND_METHOD: &str = "send_dfx";
@@ -50,7 +51,7 @@ pub async fn exec(env: &dyn Environment, opts: TransferOpts) -> DfxResult {
     let amount = get_icpts_from_args(opts.amount, opts.icp, opts.e8s)?;
 
     let fee = opts.fee.map_or(Ok(TRANSACTION_FEE), |v| {
-        ICPTs::from_str(&v).map_err(|err| anyhow!(err))
+        parse_icpts(&v).map_err(|err| anyhow!(err))
     })?;
 
     // validated by memo_validator
@@ -64,7 +65,7 @@ pub async fn exec(env: &dyn Environment, opts: TransferOpts) -> DfxResult {
 
     fetch_root_key_if_needed(env).await?;
 
-    let canister_id = Principal::from_text(LEDGER_CANISTER_ID)?;
+    let canister_id = get_ledger_defaults(env).get_ledger_canister_id()?;
 
     let result = agent
         .update(&canister_id, SEND_METHOD)
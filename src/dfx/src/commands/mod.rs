@@ -7,15 +7,20 @@ mod bootstrap;
 mod build;
 mod cache;
 mod canister;
+mod check;
+mod clean;
 mod config;
 mod deploy;
 mod generate;
 mod identity;
+mod info;
 mod language_service;
 mod ledger;
 mod new;
 mod ping;
+mod plugin;
 mod replica;
+mod selftest;
 mod start;
 mod stop;
 mod toolchain;
@@ -28,21 +33,28 @@ pub enum Command {
     Build(build::CanisterBuildOpts),
     Cache(cache::CacheOpts),
     Canister(canister::CanisterOpts),
+    Check(check::CheckOpts),
+    Clean(clean::CleanOpts),
     Config(config::ConfigOpts),
     Deploy(deploy::DeployOpts),
     Generate(generate::GenerateOpts),
     Identity(identity::IdentityOpt),
+    Info(info::InfoOpts),
     #[clap(name("_language-service"))]
     LanguageServices(language_service::LanguageServiceOpts),
     Ledger(ledger::LedgerOpts),
     New(new::NewOpts),
     Ping(ping::PingOpts),
     Replica(replica::ReplicaOpts),
+    Selftest(selftest::SelftestOpts),
     Start(start::StartOpts),
     Stop(stop::StopOpts),
     Toolchain(toolchain::ToolchainOpts),
     Upgrade(upgrade::UpgradeOpts),
     Wallet(wallet::WalletOpts),
+    /// An unknown command, run through the `dfx-<command>` plugin binary.
+    #[clap(external_subcommand)]
+    Plugin(Vec<String>),
 }
 
 pub fn exec(env: &dyn Environment, cmd: Command) -> DfxResult {
@@ -51,19 +63,24 @@ pub fn exec(env: &dyn Environment, cmd: Command) -> DfxResult {
         Command::Build(v) => build::exec(env, v),
         Command::Cache(v) => cache::exec(env, v),
         Command::Canister(v) => canister::exec(env, v),
+        Command::Check(v) => check::exec(env, v),
+        Command::Clean(v) => clean::exec(env, v),
         Command::Config(v) => config::exec(env, v),
         Command::Deploy(v) => deploy::exec(env, v),
         Command::Generate(v) => generate::exec(env, v),
         Command::Identity(v) => identity::exec(env, v),
+        Command::Info(v) => info::exec(env, v),
         Command::LanguageServices(v) => language_service::exec(env, v),
         Command::Ledger(v) => ledger::exec(env, v),
         Command::New(v) => new::exec(env, v),
         Command::Ping(v) => ping::exec(env, v),
         Command::Replica(v) => replica::exec(env, v),
+        Command::Selftest(v) => selftest::exec(env, v),
         Command::Start(v) => start::exec(env, v),
         Command::Stop(v) => stop::exec(env, v),
         Command::Toolchain(v) => toolchain::exec(env, v),
         Command::Upgrade(v) => upgrade::exec(env, v),
         Command::Wallet(v) => wallet::exec(env, v),
+        Command::Plugin(v) => plugin::exec(env, v),
     }
 }
@@ -5,12 +5,15 @@ use crate::lib::network::network_descriptor::NetworkDescriptor;
 use crate::lib::provider::{
     command_line_provider_to_url, get_network_context, get_network_descriptor,
 };
+use crate::lib::waiter::retry_transient;
 use crate::util::expiry_duration;
 
 use anyhow::anyhow;
 use clap::Clap;
-use garcon::{Delay, Waiter};
+use garcon::Delay;
+use ic_agent::AgentError;
 use slog::warn;
+use std::time::Instant;
 use tokio::runtime::Runtime;
 
 /// Pings an Internet Computer network and returns its status.
@@ -28,8 +31,7 @@ pub struct PingOpts {
 }
 
 pub fn exec(env: &dyn Environment, opts: PingOpts) -> DfxResult {
-    env.get_config()
-        .ok_or_else(|| anyhow!("Cannot find dfx configuration file in the current working directory. Did you forget to create one?"))?;
+    env.get_config_or_anyhow()?;
 
     // For ping, "provider" could either be a URL or a network name.
     // If not passed, we default to the "local" network.
@@ -61,31 +63,41 @@ pub fn exec(env: &dyn Environment, opts: PingOpts) -> DfxResult {
             .timeout(std::time::Duration::from_secs(60))
             .throttle(std::time::Duration::from_secs(1))
             .build();
-        waiter.start();
-
-        loop {
-            let status = runtime.block_on(agent.status());
-            if let Ok(status) = status {
+        let (status, start) = runtime
+            .block_on(retry_transient(&mut waiter, || async {
+                let start = Instant::now();
+                let status = agent.status().await?;
                 let healthy = match &status.replica_health_status {
                     Some(s) if s == "healthy" => true,
                     None => true,
                     _ => false,
                 };
                 if healthy {
-                    println!("{}", status);
-                    break;
+                    Ok(Some((status, start)))
                 } else {
                     eprintln!("{}", status);
+                    Ok(None)
+                }
+            }))
+            .map_err(|err| match err {
+                AgentError::TimeoutWaitingForResponse() => {
+                    anyhow!("Timed out waiting for replica to become healthy")
                 }
-            }
-            waiter
-                .wait()
-                .map_err(|_| anyhow!("Timed out waiting for replica to become healthy"))?;
-        }
+                err => err.into(),
+            })?;
+        println!("{}", status);
+        print_round_trip_time(start);
     } else {
+        let start = Instant::now();
         let status = runtime.block_on(agent.status())?;
         println!("{}", status);
+        print_round_trip_time(start);
     }
 
     Ok(())
 }
+
+/// Print the time the status request took to stderr, so the status on stdout stays parseable.
+fn print_round_trip_time(start: Instant) {
+    eprintln!("Round-trip time: {} ms", start.elapsed().as_millis());
+}
@@ -0,0 +1,97 @@
+use crate::error_invalid_argument;
+use crate::lib::environment::Environment;
+use crate::lib::error::{ChildExitError, DfxResult};
+
+use anyhow::Context;
+use clap::IntoApp;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Run `dfx <name> <args>...` for an unknown command `<name>` by executing the `dfx-<name>`
+/// plugin binary, found in the `bin/` directory of the project or on the PATH, like git does
+/// for its subcommands. The plugin gets the context of dfx through environment variables.
+pub fn exec(env: &dyn Environment, args: Vec<String>) -> DfxResult {
+    let (name, args) = args
+        .split_first()
+        .ok_or_else(|| error_invalid_argument!("Missing command."))?;
+
+    let plugin_name = format!("dfx-{}", name);
+    let plugin = match find_plugin(env, &plugin_name) {
+        Some(plugin) => plugin,
+        None => {
+            return Err(error_invalid_argument!(
+                "Unknown command '{}'. The available commands are: {}.",
+                name,
+                builtin_command_names().join(", ")
+            ))
+        }
+    };
+
+    let mut command = Command::new(&plugin);
+    command.args(args);
+    let config = env.get_config();
+    let default_network = config
+        .as_ref()
+        .map_or("local", |config| config.get_config().get_default_network());
+    command.env(
+        "DFX_NETWORK",
+        get_network_arg(args).unwrap_or(default_network),
+    );
+    if let Some(config) = config {
+        command.env("DFX_PROJECT_ROOT", config.get_project_root());
+        command.env("DFX_CONFIG_PATH", config.get_path());
+    }
+    if let Some(identity) = env.get_identity_override() {
+        command.env("DFX_IDENTITY", identity);
+    }
+
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run plugin '{}'.", plugin.display()))?;
+    if !status.success() {
+        return Err(ChildExitError(plugin_name, status.code().unwrap_or(1)).into());
+    }
+    Ok(())
+}
+
+/// Look for an executable plugin in the `bin/` directory of the project first, then on the PATH.
+fn find_plugin(env: &dyn Environment, plugin_name: &str) -> Option<PathBuf> {
+    let project_bin = env
+        .get_config()
+        .map(|config| config.get_project_root().join("bin"));
+    let path_dirs = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    project_bin
+        .into_iter()
+        .chain(path_dirs)
+        .map(|dir| dir.join(plugin_name))
+        .find(|path| is_executable(path))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// The value of the `--network` argument passed to the plugin, if any.
+fn get_network_arg(args: &[String]) -> Option<&str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--network" {
+            args.get(i + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix("--network=")
+        }
+    })
+}
+
+fn builtin_command_names() -> Vec<String> {
+    crate::CliOpts::into_app()
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .filter(|name| !name.starts_with('_'))
+        .collect()
+}
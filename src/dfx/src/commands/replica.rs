@@ -10,9 +10,14 @@ use actix::Addr;
 use clap::Clap;
 use std::default::Default;
 
+mod logs;
+
 /// Starts a local Internet Computer replica.
 #[derive(Clap)]
 pub struct ReplicaOpts {
+    #[clap(subcommand)]
+    subcmd: Option<SubCommand>,
+
     /// Specifies the port the local replica should listen to.
     #[clap(long)]
     port: Option<String>,
@@ -26,6 +31,11 @@ pub struct ReplicaOpts {
     no_artificial_delay: bool,
 }
 
+#[derive(Clap)]
+enum SubCommand {
+    Logs(logs::ReplicaLogsOpts),
+}
+
 /// Gets the configuration options for the Internet Computer replica.
 fn get_config(env: &dyn Environment, opts: ReplicaOpts) -> DfxResult<ReplicaConfig> {
     let config = get_config_from_file(env);
@@ -79,7 +89,11 @@ fn start_replica(
 /// Start the Internet Computer locally. Spawns a proxy to forward and
 /// manage browser requests. Responsible for running the network (one
 /// replica at the moment) and the proxy.
-pub fn exec(env: &dyn Environment, opts: ReplicaOpts) -> DfxResult {
+pub fn exec(env: &dyn Environment, mut opts: ReplicaOpts) -> DfxResult {
+    if let Some(SubCommand::Logs(v)) = opts.subcmd.take() {
+        return logs::exec(env, v);
+    }
+
     let system = actix::System::new("dfx-replica");
     let shutdown_controller = start_shutdown_controller(env)?;
     if opts.emulator {
@@ -0,0 +1,57 @@
+use crate::commands::start::BACKGROUND_LOG_FILE_NAME;
+use crate::commands::stop::{is_running, read_running_pid};
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+
+use anyhow::{bail, Context};
+use clap::Clap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::time::Duration;
+
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Prints the logs of the replica started with `dfx start --background`.
+#[derive(Clap)]
+pub struct ReplicaLogsOpts {
+    /// Keeps printing new lines as they are logged, until the replica stops or Ctrl-C is pressed.
+    #[clap(long, short('f'))]
+    follow: bool,
+}
+
+pub fn exec(env: &dyn Environment, opts: ReplicaLogsOpts) -> DfxResult {
+    let temp_dir = env.get_temp_dir();
+    let pid = match read_running_pid(&temp_dir.join("pid")) {
+        Some(pid) => pid,
+        None => {
+            bail!("No local network replica is running. Start one with 'dfx start --background'.")
+        }
+    };
+
+    let log_path = temp_dir.join(BACKGROUND_LOG_FILE_NAME);
+    let mut log_file = std::fs::File::open(&log_path).with_context(|| {
+        format!(
+            "Cannot open {}. Only the logs of a replica started with 'dfx start --background' are kept.",
+            log_path.display()
+        )
+    })?;
+
+    let mut stdout = std::io::stdout();
+    let mut position = std::io::copy(&mut log_file, &mut stdout)?;
+    stdout.flush()?;
+
+    while opts.follow && is_running(pid) {
+        std::thread::sleep(FOLLOW_INTERVAL);
+        // Start over if the log file was recreated by another `dfx start --background`.
+        if std::fs::metadata(&log_path)?.len() < position {
+            log_file = std::fs::File::open(&log_path)?;
+            position = 0;
+        }
+        log_file.seek(SeekFrom::Start(position))?;
+        let mut new_output = vec![];
+        position += log_file.read_to_end(&mut new_output)? as u64;
+        stdout.write_all(&new_output)?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::lib::builders::compile_standalone;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::util::check_candid_file;
+
+use anyhow::{anyhow, bail};
+use clap::Clap;
+use std::path::Path;
+
+const SELFTEST_MAIN: &str = r#"actor {
+    public func greet(name : Text) : async Text {
+        return "Hello, " # name # "!";
+    };
+};
+"#;
+
+/// Checks that the toolchain of this dfx version works, by compiling a trivial canister in a
+/// temporary directory. Prints the compiler output if it fails.
+#[derive(Clap)]
+pub struct SelftestOpts {}
+
+pub fn exec(env: &dyn Environment, _opts: SelftestOpts) -> DfxResult {
+    let cache = env.get_cache();
+    cache.install()?;
+
+    // The directory and everything in it is removed when it goes out of scope.
+    let temp_dir = tempfile::tempdir()?;
+    let main_path = temp_dir.path().join("main.mo");
+    std::fs::write(&main_path, SELFTEST_MAIN)?;
+    let wasm_path = temp_dir.path().join("main.wasm");
+    let idl_path = temp_dir.path().join("main.did");
+    let js_path = temp_dir.path().join("main.did.js");
+
+    let result = (|| -> DfxResult {
+        compile_standalone(
+            env.get_logger(),
+            cache.as_ref(),
+            &main_path,
+            &wasm_path,
+            &idl_path,
+        )?;
+
+        let (env, ty) = check_candid_file(&idl_path)?;
+        std::fs::write(&js_path, candid::bindings::javascript::compile(&env, &ty))?;
+
+        for path in &[&wasm_path, &idl_path, &js_path] {
+            ensure_not_empty(path)?;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => {
+            println!(
+                "PASS: dfx {} built a canister successfully.",
+                cache.version_str()
+            );
+            Ok(())
+        }
+        Err(err) => {
+            println!(
+                "FAIL: dfx {} could not build a canister.",
+                cache.version_str()
+            );
+            Err(err)
+        }
+    }
+}
+
+fn ensure_not_empty(path: &Path) -> DfxResult {
+    let metadata = std::fs::metadata(path)
+        .map_err(|_| anyhow!("Expected '{}' to be produced.", path.display()))?;
+    if metadata.len() == 0 {
+        bail!("Expected '{}' not to be empty.", path.display());
+    }
+    Ok(())
+}
@@ -7,6 +7,7 @@ use crate::config::dfinity::Config;
 use crate::lib::environment::Environment;
 use crate::lib::error::{DfxError, DfxResult};
 use crate::lib::replica_config::ReplicaConfig;
+use crate::lib::waiter::retry_transient;
 use crate::util::get_reusable_socket_addr;
 
 use crate::actors::icx_proxy::IcxProxyConfig;
@@ -27,6 +28,10 @@ use std::process::Command;
 use sysinfo::{System, SystemExt};
 use tokio::runtime::Runtime;
 
+/// The file in the temporary directory of the project that the output of `dfx start
+/// --background`, including the output of the replica, is written to.
+pub const BACKGROUND_LOG_FILE_NAME: &str = "replica.log";
+
 /// Starts the local replica and a web server for the current project.
 #[derive(Clap)]
 pub struct StartOpts {
@@ -66,26 +71,16 @@ fn ping_and_wait(frontend_url: &str) -> DfxResult {
         .throttle(std::time::Duration::from_secs(1))
         .build();
 
-    runtime.block_on(async {
-        waiter.start();
-        loop {
-            let status = agent.status().await;
-            if let Ok(status) = &status {
-                let healthy = match &status.replica_health_status {
-                    Some(status) if status == "healthy" => true,
-                    None => true, // emulator doesn't report replica_health_status
-                    _ => false,
-                };
-                if healthy {
-                    break;
-                }
-            }
-            waiter
-                .wait()
-                .map_err(|_| DfxError::new(status.unwrap_err()))?;
-        }
-        Ok(())
-    })
+    runtime.block_on(retry_transient(&mut waiter, || async {
+        let status = agent.status().await?;
+        let healthy = match &status.replica_health_status {
+            Some(status) if status == "healthy" => true,
+            None => true, // emulator doesn't report replica_health_status
+            _ => false,
+        };
+        Ok(if healthy { Some(()) } else { None })
+    }))?;
+    Ok(())
 }
 
 // The frontend webserver is brought up by the bg process; thus, the fg process
@@ -127,7 +122,8 @@ fn fg_ping_and_wait(webserver_port_path: PathBuf, frontend_url: String) -> DfxRe
 /// replica at the moment) and the proxy.
 pub fn exec(env: &dyn Environment, opts: StartOpts) -> DfxResult {
     let config = env.get_config_or_anyhow()?;
-    let network_descriptor = get_network_descriptor(env, None)?;
+    // The replica always serves the local network, whatever the default network of the project.
+    let network_descriptor = get_network_descriptor(env, Some("local".to_string()))?;
     let temp_dir = env.get_temp_dir();
     let build_output_root = temp_dir.join(&network_descriptor.name).join("canisters");
     let pid_file_path = temp_dir.join("pid");
@@ -151,7 +147,7 @@ pub fn exec(env: &dyn Environment, opts: StartOpts) -> DfxResult {
     let (frontend_url, address_and_port) = frontend_address(opts.host, &config, background)?;
 
     if background {
-        send_background()?;
+        send_background(&temp_dir.join(BACKGROUND_LOG_FILE_NAME))?;
         return fg_ping_and_wait(webserver_port_path, frontend_url);
     }
 
@@ -241,7 +237,7 @@ pub fn start_webserver_coordinator(
     Ok(ProxyWebserverCoordinator::new(actor_config).start())
 }
 
-fn send_background() -> DfxResult<()> {
+fn send_background(log_path: &Path) -> DfxResult<()> {
     // Background strategy is different; we spawn `dfx` with the same arguments
     // (minus --background), ping and exit.
     let exe = std::env::current_exe()?;
@@ -249,6 +245,12 @@ fn send_background() -> DfxResult<()> {
     // Skip 1 because arg0 is this executable's path.
     cmd.args(std::env::args().skip(1).filter(|a| !a.eq("--background")));
 
+    // The output is written to a file, which `dfx replica logs` reads.
+    let log_file = fs::File::create(log_path)
+        .with_context(|| format!("Failed to create {}.", log_path.display()))?;
+    cmd.stdout(log_file.try_clone()?);
+    cmd.stderr(log_file);
+
     cmd.spawn()?;
     Ok(())
 }
@@ -267,6 +269,7 @@ fn frontend_address(
                 .expect("could not get socket_addr"))
         })
         .map_err(|e| anyhow!("Invalid argument: Invalid host: {}", e))?;
+    check_address_available(address_and_port)?;
 
     if !background {
         // Since the user may have provided port "0", we need to grab a dynamically
@@ -284,6 +287,22 @@ fn frontend_address(
     Ok((frontend_url, address_and_port))
 }
 
+/// Fail with a helpful error if another process already listens on `address`, instead of
+/// letting the webserver fail to start later on.
+fn check_address_available(address: SocketAddr) -> DfxResult {
+    if address.port() == 0 {
+        return Ok(());
+    }
+    match std::net::TcpListener::bind(address) {
+        Err(err) if err.kind() == std::io::ErrorKind::AddrInUse => bail!(
+            "Cannot start the local network on {}: the address is already in use. Stop the process using it (e.g. with 'dfx stop' if it is another dfx), or pick another address with --host or 'networks.local.bind' in dfx.json.",
+            address
+        ),
+        // Any other problem is reported when actually binding.
+        _ => Ok(()),
+    }
+}
+
 fn check_previous_process_running(dfx_pid_path: &Path) -> DfxResult<()> {
     if dfx_pid_path.exists() {
         // Read and verify it's not running. If it is just return.
@@ -305,3 +324,24 @@ fn write_pid(pid_file_path: &Path) {
         let _ = std::fs::write(&pid_file_path, pid.to_string());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_in_use_is_reported() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let err = check_address_available(address).unwrap_err();
+        assert!(err.to_string().contains("already in use"));
+
+        drop(listener);
+        check_address_available(address).unwrap();
+    }
+
+    #[test]
+    fn dynamic_port_is_always_available() {
+        check_address_available("127.0.0.1:0".parse().unwrap()).unwrap();
+    }
+}
@@ -2,6 +2,7 @@ use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 
 use clap::Clap;
+use std::path::Path;
 use sysinfo::{Pid, Process, ProcessExt, Signal, System, SystemExt};
 
 /// Stops the local network replica.
@@ -35,17 +36,32 @@ fn kill_all(pid: Pid) -> DfxResult {
     Ok(())
 }
 
+/// Return the pid written by `dfx start` if that process is still running.
+pub fn read_running_pid(pid_file_path: &Path) -> Option<Pid> {
+    let pid = std::fs::read_to_string(pid_file_path)
+        .ok()?
+        .parse::<Pid>()
+        .ok()?;
+    if is_running(pid) {
+        Some(pid)
+    } else {
+        None
+    }
+}
+
+pub fn is_running(pid: Pid) -> bool {
+    System::new().get_process(pid).is_some()
+}
+
 pub fn exec(env: &dyn Environment, _opts: StopOpts) -> DfxResult {
     let pid_file_path = env.get_temp_dir().join("pid");
-    if pid_file_path.exists() {
-        // Read and verify it's not running. If it is just return.
-        if let Ok(s) = std::fs::read_to_string(&pid_file_path) {
-            if let Ok(pid) = s.parse::<i32>() {
-                kill_all(pid)?;
-            }
-        }
-    } else {
+    if !pid_file_path.exists() {
         eprintln!("No local network replica found. Nothing to do.");
+    } else if let Some(pid) = read_running_pid(&pid_file_path) {
+        kill_all(pid)?;
+    } else {
+        // dfx start was killed without cleaning up, e.g. by a reboot.
+        eprintln!("The local network replica is not running anymore. Nothing to do.");
     }
 
     // We ignore errors here because there is no effect for the user. We're just being nice.
@@ -53,3 +69,31 @@ pub fn exec(env: &dyn Environment, _opts: StopOpts) -> DfxResult {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pid_of_running_process_is_read() {
+        let pid_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(pid_file.path(), std::process::id().to_string()).unwrap();
+        assert_eq!(
+            read_running_pid(pid_file.path()),
+            Some(std::process::id() as Pid)
+        );
+    }
+
+    #[test]
+    fn stale_or_invalid_pid_file_is_ignored() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+        let pid_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(pid_file.path(), child.id().to_string()).unwrap();
+        assert_eq!(read_running_pid(pid_file.path()), None);
+
+        std::fs::write(pid_file.path(), "").unwrap();
+        assert_eq!(read_running_pid(pid_file.path()), None);
+        assert_eq!(read_running_pid(&pid_file.path().join("missing")), None);
+    }
+}
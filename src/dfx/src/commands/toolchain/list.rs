@@ -3,16 +3,27 @@ use crate::lib::error::DfxResult;
 use crate::lib::toolchain;
 
 use clap::Clap;
+use std::io::Write;
 
 /// List installed toolchains
 #[derive(Clap)]
 #[clap(name("list"))]
-pub struct ToolchainList {}
+pub struct ToolchainList {
+    /// Separates toolchain names with a NUL byte instead of a newline.
+    /// Useful in conjunction with `xargs -0`.
+    #[clap(long)]
+    print0: bool,
+}
 
-pub fn exec(_env: &dyn Environment, _opts: ToolchainList) -> DfxResult {
+pub fn exec(_env: &dyn Environment, opts: ToolchainList) -> DfxResult {
     let toolchains = toolchain::list_installed_toolchains()?;
     for toolchain in toolchains {
-        println!("{}", toolchain);
+        if opts.print0 {
+            print!("{}\0", toolchain);
+        } else {
+            println!("{}", toolchain);
+        }
     }
+    std::io::stdout().flush()?;
     Ok(())
 }
@@ -4,16 +4,24 @@ use crate::util;
 
 use anyhow::bail;
 use indicatif::{ProgressBar, ProgressDrawTarget};
+use openssl::sha::Sha256;
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
 use semver::Version;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::Read;
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitStatus;
+use std::sync::Arc;
 
 // POSIX permissions for files in the cache.
 const EXEC_READ_USER_ONLY_PERMISSION: u32 = 0o500;
 
+// The file listing the SHA-256 of the files of a version, as `<sha256>  <file name>` lines.
+const CHECKSUMS_FILE_NAME: &str = "SHA256SUMS";
+
 pub trait Cache {
     fn version_str(&self) -> String;
     fn is_installed(&self) -> DfxResult<bool>;
@@ -24,14 +32,29 @@ pub trait Cache {
     fn get_binary_command(&self, binary_name: &str) -> DfxResult<std::process::Command>;
 }
 
+/// Notified when a version of the SDK finishes installing into the cache, e.g. to warm other
+/// caches. The default implementation does nothing.
+pub trait InstallObserver {
+    fn installed(&self, _version: &str, _binaries: &[PathBuf]) {}
+}
+
 pub struct DiskBasedCache {
     version: Version,
+    install_observer: Option<Arc<dyn InstallObserver>>,
 }
 
 impl DiskBasedCache {
     pub fn with_version(version: &Version) -> DiskBasedCache {
         DiskBasedCache {
             version: version.clone(),
+            install_observer: None,
+        }
+    }
+
+    pub fn with_install_observer(self, install_observer: Arc<dyn InstallObserver>) -> Self {
+        DiskBasedCache {
+            install_observer: Some(install_observer),
+            ..self
         }
     }
 }
@@ -47,14 +70,14 @@ impl Cache for DiskBasedCache {
     }
 
     fn install(&self) -> DfxResult {
-        install_version(&self.version_str(), false).map(|_| {})
+        install_version(&self.version_str(), false, self.install_observer.as_deref()).map(|_| {})
     }
     fn force_install(&self) -> DfxResult {
-        install_version(&self.version_str(), true).map(|_| {})
+        install_version(&self.version_str(), true, self.install_observer.as_deref()).map(|_| {})
     }
 
     fn delete(&self) -> DfxResult {
-        delete_version(&self.version_str()).map(|_| {})
+        delete_version(&self.version_str(), true).map(|_| {})
     }
 
     fn get_binary_command_path(&self, binary_name: &str) -> DfxResult<PathBuf> {
@@ -67,11 +90,11 @@ impl Cache for DiskBasedCache {
 }
 
 pub fn get_cache_root() -> DfxResult<PathBuf> {
-    let config_root = std::env::var("DFX_CONFIG_ROOT").ok();
-    let home =
-        std::env::var("HOME").map_err(|_| DfxError::new(CacheError::CannotFindHomeDirectory()))?;
-    let root = config_root.unwrap_or(home);
-    let p = PathBuf::from(root).join(".cache").join("dfinity");
+    let p = cache_root_path(
+        std::env::var_os("DFX_CACHE_ROOT"),
+        std::env::var_os("DFX_CONFIG_ROOT"),
+        std::env::var_os("HOME"),
+    )?;
     if !p.exists() {
         if let Err(_e) = std::fs::create_dir_all(&p) {
             return Err(DfxError::new(CacheError::CannotCreateCacheDirectory(p)));
@@ -82,6 +105,23 @@ pub fn get_cache_root() -> DfxResult<PathBuf> {
     Ok(p)
 }
 
+/// The cache root is `$DFX_CACHE_ROOT` if it is set, e.g. when the home directory is read-only,
+/// and `.cache/dfinity` in `$DFX_CONFIG_ROOT` or the home directory otherwise.
+fn cache_root_path(
+    cache_root: Option<OsString>,
+    config_root: Option<OsString>,
+    home: Option<OsString>,
+) -> DfxResult<PathBuf> {
+    if let Some(cache_root) = cache_root {
+        return Ok(PathBuf::from(cache_root));
+    }
+
+    let root = config_root
+        .or(home)
+        .ok_or_else(|| DfxError::new(CacheError::CannotFindHomeDirectory()))?;
+    Ok(PathBuf::from(root).join(".cache").join("dfinity"))
+}
+
 /// Return the binary cache root. It constructs it if not present
 /// already.
 pub fn get_bin_cache_root() -> DfxResult<PathBuf> {
@@ -107,18 +147,37 @@ pub fn is_version_installed(v: &str) -> DfxResult<bool> {
     get_bin_cache(v).map(|c| c.is_dir())
 }
 
-pub fn delete_version(v: &str) -> DfxResult<bool> {
-    if !is_version_installed(v).unwrap_or(false) {
+/// Delete a version from the cache, returning whether it was installed. The version of dfx
+/// being run is only deleted with `force`, as its binaries may still be in use, e.g. by a
+/// replica started with `dfx start --background`.
+pub fn delete_version(v: &str, force: bool) -> DfxResult<bool> {
+    if !force && Version::parse(v).map_or(false, |v| v == *dfx_version()) {
+        return Err(DfxError::new(CacheError::CannotDeleteRunningVersion(
+            v.to_owned(),
+        )));
+    }
+
+    delete_version_in(&get_bin_cache_root()?, v)
+}
+
+fn delete_version_in(root: &Path, v: &str) -> DfxResult<bool> {
+    let p = root.join(v);
+    if !p.is_dir() {
         return Ok(false);
     }
 
-    let root = get_bin_cache(v)?;
-    std::fs::remove_dir_all(&root)?;
+    std::fs::remove_dir_all(&p)?;
 
     Ok(true)
 }
 
-pub fn install_version(v: &str, force: bool) -> DfxResult<PathBuf> {
+/// Install a version into the cache. If it was not already installed, `observer` is notified
+/// with the paths of the installed binaries.
+pub fn install_version(
+    v: &str,
+    force: bool,
+    observer: Option<&dyn InstallObserver>,
+) -> DfxResult<PathBuf> {
     let p = get_bin_cache(v)?;
     if !force && is_version_installed(v).unwrap_or(false) {
         return Ok(p);
@@ -141,31 +200,9 @@ pub fn install_version(v: &str, force: bool) -> DfxResult<PathBuf> {
 
         let rand_string: String = thread_rng().sample_iter(&Alphanumeric).take(12).collect();
         let temp_p = get_bin_cache(&format!("_{}_{}", v, rand_string))?;
-        std::fs::create_dir(&temp_p)?;
-
-        let mut binary_cache_assets = util::assets::binary_cache()?;
-        // Write binaries and set them to be executable.
-        for file in binary_cache_assets.entries()? {
-            let mut file = file?;
-
-            if file.header().entry_type().is_dir() {
-                continue;
-            }
-            file.unpack_in(temp_p.as_path())?;
-
-            let full_path = temp_p.join(file.path()?);
-            let mut perms = std::fs::metadata(full_path.as_path())?.permissions();
-            perms.set_mode(EXEC_READ_USER_ONLY_PERMISSION);
-            std::fs::set_permissions(full_path.as_path(), perms)?;
-        }
-
-        // Copy our own binary in the cache.
-        let dfx = temp_p.join("dfx");
-        std::fs::write(&dfx, std::fs::read(current_exe)?)?;
-        // And make it executable.
-        let mut perms = std::fs::metadata(&dfx)?.permissions();
-        perms.set_mode(EXEC_READ_USER_ONLY_PERMISSION);
-        std::fs::set_permissions(&dfx, perms)?;
+        let binaries = populate_temp_dir(&temp_p, |temp_p| {
+            write_version_files(temp_p, v, &current_exe)
+        })?;
 
         // atomically install cache version into place
         if force && p.exists() {
@@ -176,6 +213,10 @@ pub fn install_version(v: &str, force: bool) -> DfxResult<PathBuf> {
             if let Some(b) = b {
                 b.finish_with_message(&format!("Version v{} installed successfully.", v));
             }
+            if let Some(observer) = observer {
+                let binaries: Vec<PathBuf> = binaries.iter().map(|path| p.join(path)).collect();
+                observer.installed(v, &binaries);
+            }
         } else {
             std::fs::remove_dir_all(&temp_p)?;
             if let Some(b) = b {
@@ -189,10 +230,233 @@ pub fn install_version(v: &str, force: bool) -> DfxResult<PathBuf> {
     }
 }
 
+/// Run `populate` on a new temporary directory, and remove the directory if it fails, so an
+/// install that fails halfway does not leave a partially populated version behind.
+fn populate_temp_dir<T>(
+    temp_p: &Path,
+    populate: impl FnOnce(&Path) -> DfxResult<T>,
+) -> DfxResult<T> {
+    std::fs::create_dir(temp_p)?;
+    let result = populate(temp_p);
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(temp_p);
+    }
+    result
+}
+
+/// Write the binaries of version `v`, which must be the version being run, and their
+/// checksums to `temp_p`. Returns the paths of the binaries relative to `temp_p`.
+fn write_version_files(temp_p: &Path, v: &str, current_exe: &Path) -> DfxResult<Vec<PathBuf>> {
+    let expected: HashMap<String, String> =
+        parse_checksums(util::assets::binary_cache_checksums())?
+            .into_iter()
+            .collect();
+    let mut binaries = vec![PathBuf::from("dfx")];
+    let mut checksums = String::new();
+    let mut binary_cache_assets = util::assets::binary_cache()?;
+    // Write binaries and set them to be executable.
+    for file in binary_cache_assets.entries()? {
+        let mut file = file?;
+
+        if file.header().entry_type().is_dir() {
+            continue;
+        }
+        let entry_path = file.path()?.to_path_buf();
+        let entry_path = entry_path
+            .strip_prefix(".")
+            .unwrap_or(&entry_path)
+            .to_path_buf();
+        let full_path = temp_p.join(&entry_path);
+        if file.header().entry_type().is_file() {
+            let mut content = vec![];
+            file.read_to_end(&mut content)?;
+            check_bundled_checksum(&expected, &entry_path, &content)?;
+            if let Some(parent) = full_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&full_path, &content)?;
+            checksums.push_str(&checksum_line(&content, &entry_path));
+        } else {
+            file.unpack_in(temp_p)?;
+        }
+
+        let mut perms = std::fs::metadata(full_path.as_path())?.permissions();
+        perms.set_mode(EXEC_READ_USER_ONLY_PERMISSION);
+        std::fs::set_permissions(full_path.as_path(), perms)?;
+        binaries.push(entry_path);
+    }
+
+    // Copy our own binary in the cache.
+    let dfx = temp_p.join("dfx");
+    let dfx_content = std::fs::read(current_exe)?;
+    std::fs::write(&dfx, &dfx_content)?;
+    // And make it executable.
+    let mut perms = std::fs::metadata(&dfx)?.permissions();
+    perms.set_mode(EXEC_READ_USER_ONLY_PERMISSION);
+    std::fs::set_permissions(&dfx, perms)?;
+    checksums.push_str(&checksum_line(&dfx_content, Path::new("dfx")));
+
+    // Check what was written against the checksums of the bundled binaries before
+    // installing it, so a failed write does not leave a broken version in the cache.
+    std::fs::write(temp_p.join(CHECKSUMS_FILE_NAME), checksums)?;
+    verify_dir(temp_p, v)?;
+
+    Ok(binaries)
+}
+
+/// Check a binary unpacked from the binary cache bundled with dfx against the checksums dfx
+/// was built with, so a corrupted dfx does not install corrupted binaries.
+fn check_bundled_checksum(
+    expected: &HashMap<String, String>,
+    path: &Path,
+    content: &[u8],
+) -> DfxResult {
+    let file_name = path.to_string_lossy();
+    if expected.get(file_name.as_ref()) == Some(&sha256_hex(content)) {
+        Ok(())
+    } else {
+        Err(DfxError::new(CacheError::CorruptedBinaryCache(
+            file_name.into_owned(),
+        )))
+    }
+}
+
+/// Check the files of an installed version against the checksums recorded in its
+/// `SHA256SUMS` file when it was installed. Returns `false` if the version was installed by an
+/// older dfx, which did not record checksums, so it cannot be verified.
+pub fn verify_version(v: &str) -> DfxResult<bool> {
+    let p = get_bin_cache(v)?;
+    if !p.is_dir() {
+        return Err(DfxError::new(CacheError::VersionNotInstalled(v.to_owned())));
+    }
+
+    verify_dir(&p, v)
+}
+
+fn verify_dir(dir: &Path, v: &str) -> DfxResult<bool> {
+    let checksums = match std::fs::read_to_string(dir.join(CHECKSUMS_FILE_NAME)) {
+        Ok(checksums) => checksums,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut mismatched = vec![];
+    for (file_name, expected) in parse_checksums(&checksums)? {
+        let matches = std::fs::read(dir.join(&file_name))
+            .map_or(false, |content| sha256_hex(&content) == expected);
+        if !matches {
+            mismatched.push(file_name);
+        }
+    }
+
+    if mismatched.is_empty() {
+        Ok(true)
+    } else {
+        Err(DfxError::new(CacheError::ChecksumMismatch(
+            v.to_owned(),
+            mismatched.join(", "),
+        )))
+    }
+}
+
+/// Parse the `<sha256>  <file name>` lines of a `SHA256SUMS` file into pairs of file name and
+/// checksum.
+fn parse_checksums(checksums: &str) -> DfxResult<Vec<(String, String)>> {
+    checksums
+        .lines()
+        .map(|line| {
+            let mut parts = line.splitn(2, "  ");
+            match (parts.next(), parts.next()) {
+                (Some(expected), Some(file_name)) => {
+                    Ok((file_name.to_owned(), expected.to_owned()))
+                }
+                _ => bail!("Invalid line in {}: '{}'.", CHECKSUMS_FILE_NAME, line),
+            }
+        })
+        .collect()
+}
+
+fn checksum_line(content: &[u8], path: &Path) -> String {
+    format!("{}  {}\n", sha256_hex(content), path.display())
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    let mut sha256 = Sha256::new();
+    sha256.update(content);
+    hex::encode(sha256.finish())
+}
+
+/// Install a single binary of a version into the cache, replacing it if it is already
+/// present. A version that is not installed yet is installed in full first, as a version
+/// directory holding a single binary would be taken for a complete install.
+pub fn install_binary(v: &str, binary_name: &str) -> DfxResult<PathBuf> {
+    if Version::parse(v)? != *dfx_version() {
+        return Err(DfxError::new(CacheError::UnknownVersion(v.to_owned())));
+    }
+
+    install_version(v, false, None)?;
+    replace_binary_in(&get_bin_cache_root()?, v, binary_name)
+}
+
+/// Replace a binary of a version installed under the binary cache root `root`. The binary is
+/// written next to the version and renamed into place, so a partially written binary is
+/// never visible in the cache.
+fn replace_binary_in(root: &Path, v: &str, binary_name: &str) -> DfxResult<PathBuf> {
+    let p = root.join(v);
+    if !p.is_dir() {
+        return Err(DfxError::new(CacheError::VersionNotInstalled(v.to_owned())));
+    }
+
+    let mut binary_cache_assets = util::assets::binary_cache()?;
+    for file in binary_cache_assets.entries()? {
+        let mut file = file?;
+
+        if file.header().entry_type().is_dir() {
+            continue;
+        }
+        let entry_path = file.path()?.to_path_buf();
+        if entry_path.strip_prefix(".").unwrap_or(&entry_path) != Path::new(binary_name) {
+            continue;
+        }
+
+        let rand_string: String = thread_rng().sample_iter(&Alphanumeric).take(12).collect();
+        let temp_p = root.join(format!("_{}_{}", v, rand_string));
+        std::fs::create_dir(&temp_p)?;
+
+        let result = (|| -> DfxResult<PathBuf> {
+            file.unpack_in(temp_p.as_path())?;
+
+            let temp_binary = temp_p.join(&entry_path);
+            let mut perms = std::fs::metadata(&temp_binary)?.permissions();
+            perms.set_mode(EXEC_READ_USER_ONLY_PERMISSION);
+            std::fs::set_permissions(&temp_binary, perms)?;
+
+            let binary_path = p.join(binary_name);
+            std::fs::rename(&temp_binary, &binary_path)?;
+            Ok(binary_path)
+        })();
+        let _ = std::fs::remove_dir_all(&temp_p);
+        return result;
+    }
+
+    Err(DfxError::new(CacheError::UnknownBinary(
+        binary_name.to_owned(),
+    )))
+}
+
 pub fn get_binary_path_from_version(version: &str, binary_name: &str) -> DfxResult<PathBuf> {
-    install_version(version, false)?;
+    install_version(version, false, None)?;
+
+    // Some entries in the cache (e.g. the motoko `base` library) are directories.
+    let path = get_bin_cache(version)?.join(binary_name);
+    if !path.exists() {
+        return Err(DfxError::new(CacheError::BinaryNotFound {
+            name: binary_name.to_owned(),
+            version: version.to_owned(),
+        }));
+    }
 
-    Ok(get_bin_cache(version)?.join(binary_name))
+    Ok(path)
 }
 
 pub fn binary_command_from_version(version: &str, name: &str) -> DfxResult<std::process::Command> {
@@ -202,17 +466,45 @@ pub fn binary_command_from_version(version: &str, name: &str) -> DfxResult<std::
     Ok(cmd)
 }
 
+/// The environment variables a command keeps when it is run with `isolate_command`.
+pub const PRESERVED_ENV_VARS: &[&str] = &["PATH"];
+
+/// Clear the environment `cmd` inherits from dfx, except for `PRESERVED_ENV_VARS`, and run it
+/// in `working_dir`, so that stray variables in the user's shell cannot change its output.
+pub fn isolate_command<'a>(
+    cmd: &'a mut std::process::Command,
+    working_dir: &Path,
+) -> &'a mut std::process::Command {
+    cmd.env_clear().current_dir(working_dir);
+    for name in PRESERVED_ENV_VARS {
+        if let Some(value) = std::env::var_os(name) {
+            cmd.env(name, value);
+        }
+    }
+    cmd
+}
+
+/// List the versions installed in the cache, newest first.
 pub fn list_versions() -> DfxResult<Vec<Version>> {
-    let root = get_bin_cache_root()?;
+    list_versions_in(&get_bin_cache_root()?)
+}
+
+/// List the versions installed in a binary cache root, newest first. Entries that are not
+/// version directories, like the temporary directories of an install in progress, are skipped.
+fn list_versions_in(root: &Path) -> DfxResult<Vec<Version>> {
     let mut result: Vec<Version> = Vec::new();
 
     for entry in std::fs::read_dir(root)? {
         let entry = entry?;
-        if let Some(version) = entry.file_name().to_str() {
-            result.push(Version::parse(version)?);
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(Ok(version)) = entry.file_name().to_str().map(Version::parse) {
+            result.push(version);
         }
     }
 
+    result.sort_by(|a, b| b.cmp(a));
     Ok(result)
 }
 
@@ -228,3 +520,211 @@ pub fn call_cached_dfx(v: &Version) -> DfxResult<ExitStatus> {
         .status()
         .map_err(DfxError::from)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::dfx_version_str;
+
+    #[test]
+    fn cache_root_path_precedence() {
+        let path = |cache_root: Option<&str>, config_root: Option<&str>, home: Option<&str>| {
+            cache_root_path(
+                cache_root.map(OsString::from),
+                config_root.map(OsString::from),
+                home.map(OsString::from),
+            )
+            .ok()
+        };
+
+        assert_eq!(
+            path(Some("/cache"), Some("/config"), Some("/home")),
+            Some(PathBuf::from("/cache"))
+        );
+        assert_eq!(
+            path(None, Some("/config"), Some("/home")),
+            Some(PathBuf::from("/config/.cache/dfinity"))
+        );
+        assert_eq!(
+            path(None, None, Some("/home")),
+            Some(PathBuf::from("/home/.cache/dfinity"))
+        );
+        assert_eq!(path(None, None, None), None);
+    }
+
+    #[test]
+    fn list_versions_newest_first() {
+        let root = tempfile::tempdir().unwrap();
+        for name in &[
+            "0.7.2",
+            "0.10.0",
+            "0.8.0-beta.1",
+            "0.8.0",
+            "_0.8.1_abcdef123456",
+        ] {
+            std::fs::create_dir(root.path().join(name)).unwrap();
+        }
+        std::fs::write(root.path().join("0.9.0"), "").unwrap();
+
+        let versions: Vec<String> = list_versions_in(root.path())
+            .unwrap()
+            .iter()
+            .map(Version::to_string)
+            .collect();
+        assert_eq!(versions, ["0.10.0", "0.8.0", "0.8.0-beta.1", "0.7.2"]);
+    }
+
+    #[test]
+    fn failed_install_leaves_nothing_behind() {
+        let root = tempfile::tempdir().unwrap();
+        let temp_p = root.path().join("_0.7.2_abcdef123456");
+
+        let result = populate_temp_dir(&temp_p, |temp_p| -> DfxResult {
+            std::fs::write(temp_p.join("moc"), "moc")?;
+            bail!("Interrupted.")
+        });
+        assert!(result.is_err());
+        assert!(!temp_p.exists());
+        assert!(list_versions_in(root.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn replace_binary_reinstalls_binary() {
+        let root = tempfile::tempdir().unwrap();
+        let v = dfx_version_str();
+        std::fs::create_dir(root.path().join(v)).unwrap();
+        std::fs::write(root.path().join(v).join("moc"), "corrupted").unwrap();
+
+        let path = replace_binary_in(root.path(), v, "moc").unwrap();
+        assert_eq!(path, root.path().join(v).join("moc"));
+        assert_ne!(std::fs::read(&path).unwrap(), b"corrupted");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, EXEC_READ_USER_ONLY_PERMISSION);
+        assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn replace_binary_rejects_unknown_binary_and_missing_version() {
+        let root = tempfile::tempdir().unwrap();
+        let v = dfx_version_str();
+
+        let err = replace_binary_in(root.path(), v, "moc").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CacheError>(),
+            Some(CacheError::VersionNotInstalled(_))
+        ));
+        assert!(!root.path().join(v).exists());
+
+        std::fs::create_dir(root.path().join(v)).unwrap();
+        let err = replace_binary_in(root.path(), v, "no-such-binary").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CacheError>(),
+            Some(CacheError::UnknownBinary(_))
+        ));
+        assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 1);
+        assert_eq!(std::fs::read_dir(root.path().join(v)).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn isolated_command_does_not_inherit_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = "echo \"${DFX_TEST_LEAKED_VAR:-unset} $(pwd)\"";
+
+        let output = std::process::Command::new("sh")
+            .env("DFX_TEST_LEAKED_VAR", "leaked")
+            .args(&["-c", script])
+            .output()
+            .unwrap();
+        assert!(String::from_utf8_lossy(&output.stdout).starts_with("leaked "));
+
+        let mut cmd = std::process::Command::new("sh");
+        cmd.env("DFX_TEST_LEAKED_VAR", "leaked");
+        let output = isolate_command(&mut cmd, dir.path())
+            .args(&["-c", script])
+            .output()
+            .unwrap();
+        let working_dir = dir.path().canonicalize().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("unset {}\n", working_dir.display())
+        );
+    }
+
+    #[test]
+    fn verify_detects_tampered_binaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let checksums = [
+            checksum_line(b"good", Path::new("moc")),
+            checksum_line(b"good", Path::new("base/List.mo")),
+            checksum_line(b"good", Path::new("ic-ref")),
+        ]
+        .concat();
+        std::fs::write(dir.path().join(CHECKSUMS_FILE_NAME), checksums).unwrap();
+        std::fs::write(dir.path().join("moc"), "good").unwrap();
+        std::fs::create_dir(dir.path().join("base")).unwrap();
+        std::fs::write(dir.path().join("base/List.mo"), "good").unwrap();
+        assert!(verify_dir(dir.path(), "0.7.2").unwrap());
+
+        std::fs::write(dir.path().join("moc"), "tampered").unwrap();
+        std::fs::remove_file(dir.path().join("base/List.mo")).unwrap();
+        let err = verify_dir(dir.path(), "0.7.2").unwrap_err();
+        match err.downcast_ref::<CacheError>() {
+            Some(CacheError::ChecksumMismatch(version, files)) => {
+                assert_eq!(version, "0.7.2");
+                assert_eq!(files, "moc, base/List.mo, ic-ref");
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn versions_without_checksums_are_unverified() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("moc"), "good").unwrap();
+        assert!(!verify_dir(dir.path(), "0.7.2").unwrap());
+    }
+
+    #[test]
+    fn bundled_binaries_are_checked_before_install() {
+        let expected: HashMap<String, String> =
+            parse_checksums(&checksum_line(b"good", Path::new("base/List.mo")))
+                .unwrap()
+                .into_iter()
+                .collect();
+        check_bundled_checksum(&expected, Path::new("base/List.mo"), b"good").unwrap();
+
+        for (path, content) in &[("base/List.mo", "corrupted"), ("moc", "good")] {
+            let err =
+                check_bundled_checksum(&expected, Path::new(path), content.as_bytes()).unwrap_err();
+            match err.downcast_ref::<CacheError>() {
+                Some(CacheError::CorruptedBinaryCache(file_name)) => assert_eq!(file_name, path),
+                _ => panic!("unexpected error: {}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn delete_installed_version() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("0.7.2").join("bin")).unwrap();
+
+        assert!(delete_version_in(root.path(), "0.7.2").unwrap());
+        assert!(!root.path().join("0.7.2").exists());
+    }
+
+    #[test]
+    fn delete_missing_version() {
+        let root = tempfile::tempdir().unwrap();
+
+        assert!(!delete_version_in(root.path(), "0.7.2").unwrap());
+    }
+
+    #[test]
+    fn delete_running_version_requires_force() {
+        let err = delete_version(&dfx_version().to_string(), false).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CacheError>(),
+            Some(CacheError::CannotDeleteRunningVersion(_))
+        ));
+    }
+}
@@ -1,20 +1,26 @@
 #![allow(dead_code)]
 use crate::lib::error::{BuildError, DfxError, DfxResult};
+use crate::lib::nns_types::{CYCLE_MINTER_CANISTER_ID, LEDGER_CANISTER_ID};
 use crate::{error_invalid_config, error_invalid_data};
 
 use anyhow::anyhow;
+use ic_types::principal::Principal;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{BTreeMap, HashSet};
 use std::default::Default;
 use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 
 pub const CONFIG_FILE_NAME: &str = "dfx.json";
+/// The environment variable naming the directory above which no dfx.json is searched for.
+pub const PROJECT_ROOT_ENV_VAR: &str = "DFX_PROJECT_ROOT";
 
 const EMPTY_CONFIG_DEFAULTS: ConfigDefaults = ConfigDefaults {
     bootstrap: None,
     build: None,
+    ledger: None,
     replica: None,
 };
 
@@ -27,6 +33,15 @@ const EMPTY_CONFIG_DEFAULTS_BOOTSTRAP: ConfigDefaultsBootstrap = ConfigDefaultsB
 const EMPTY_CONFIG_DEFAULTS_BUILD: ConfigDefaultsBuild = ConfigDefaultsBuild {
     packtool: None,
     args: None,
+    js_format: None,
+    embed_git: None,
+    moc_path: None,
+    clean_env: None,
+};
+
+const EMPTY_CONFIG_DEFAULTS_LEDGER: ConfigDefaultsLedger = ConfigDefaultsLedger {
+    ledger_canister: None,
+    cmc_canister: None,
 };
 
 const EMPTY_CONFIG_DEFAULTS_REPLICA: ConfigDefaultsReplica = ConfigDefaultsReplica {
@@ -79,6 +94,28 @@ pub struct ConfigDefaultsBootstrap {
 pub struct ConfigDefaultsBuild {
     pub packtool: Option<String>,
     pub args: Option<String>,
+    /// A command to format the generated JavaScript and TypeScript bindings with, e.g.
+    /// "npx prettier --write". The paths of the generated files are appended to it.
+    pub js_format: Option<String>,
+    /// Whether to record the git commit of the project, and whether the working tree had
+    /// uncommitted changes, in a `dfx:sources` custom section of the built wasm modules.
+    pub embed_git: Option<bool>,
+    /// A moc binary to compile Motoko canisters with instead of the one in the cache, e.g. a
+    /// locally built compiler. A relative path is relative to the project root.
+    pub moc_path: Option<PathBuf>,
+    /// Whether to run the compiler with an empty environment, except for PATH, and with the
+    /// project root as its working directory.
+    pub clean_env: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ConfigDefaultsLedger {
+    /// The principal of the ledger canister. Defaults to the ledger canister of the IC.
+    pub ledger_canister: Option<String>,
+    /// The principal of the cycles minting canister, which receives the ICP sent by
+    /// `dfx ledger create-canister` and `dfx ledger top-up` and is notified of the send.
+    /// Defaults to the cycles minting canister of the IC.
+    pub cmc_canister: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -146,10 +183,20 @@ pub enum Profile {
     Release,
 }
 
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Profile::Debug => write!(f, "debug"),
+            Profile::Release => write!(f, "release"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ConfigDefaults {
     pub bootstrap: Option<ConfigDefaultsBootstrap>,
     pub build: Option<ConfigDefaultsBuild>,
+    pub ledger: Option<ConfigDefaultsLedger>,
     pub replica: Option<ConfigDefaultsReplica>,
 }
 
@@ -161,6 +208,10 @@ pub struct ConfigInterface {
     pub canisters: Option<BTreeMap<String, ConfigCanistersCanister>>,
     pub defaults: Option<ConfigDefaults>,
     pub networks: Option<BTreeMap<String, ConfigNetwork>>,
+    /// The network used by commands that are not given a --network. Defaults to "local".
+    pub default_network: Option<String>,
+    /// Reject fields that dfx does not know about, e.g. a mistyped key.
+    pub strict: Option<bool>,
 }
 
 impl ConfigCanistersCanister {}
@@ -191,6 +242,39 @@ impl ConfigDefaultsBuild {
             _ => None,
         }
     }
+    pub fn get_js_format(&self) -> Option<String> {
+        match &self.js_format {
+            Some(v) if !v.trim().is_empty() => self.js_format.to_owned(),
+            _ => None,
+        }
+    }
+    pub fn get_embed_git(&self) -> bool {
+        self.embed_git.unwrap_or(false)
+    }
+    pub fn get_clean_env(&self) -> bool {
+        self.clean_env.unwrap_or(false)
+    }
+}
+
+impl ConfigDefaultsLedger {
+    pub fn get_ledger_canister_id(&self) -> DfxResult<Principal> {
+        parse_canister_id("ledger_canister", &self.ledger_canister, LEDGER_CANISTER_ID)
+    }
+    pub fn get_cmc_canister_id(&self) -> DfxResult<Principal> {
+        parse_canister_id("cmc_canister", &self.cmc_canister, CYCLE_MINTER_CANISTER_ID)
+    }
+}
+
+fn parse_canister_id(field: &str, value: &Option<String>, default: &str) -> DfxResult<Principal> {
+    let text = value.as_deref().unwrap_or(default);
+    Principal::from_text(text).map_err(|err| {
+        error_invalid_config!(
+            "Field 'defaults.ledger.{}' is not a valid principal '{}': {}",
+            field,
+            text,
+            err
+        )
+    })
 }
 
 impl ConfigDefaults {
@@ -206,6 +290,12 @@ impl ConfigDefaults {
             None => &EMPTY_CONFIG_DEFAULTS_BUILD,
         }
     }
+    pub fn get_ledger(&self) -> &ConfigDefaultsLedger {
+        match &self.ledger {
+            Some(x) => &x,
+            None => &EMPTY_CONFIG_DEFAULTS_LEDGER,
+        }
+    }
     pub fn get_replica(&self) -> &ConfigDefaultsReplica {
         match &self.replica {
             Some(x) => &x,
@@ -259,6 +349,10 @@ impl ConfigInterface {
         }
     }
 
+    pub fn get_default_network(&self) -> &str {
+        self.default_network.as_deref().unwrap_or("local")
+    }
+
     pub fn get_local_bind_address(&self, default: &str) -> DfxResult<SocketAddr> {
         self.get_network("local")
             .map(|network| match network {
@@ -294,12 +388,79 @@ impl ConfigInterface {
                 add_dependencies(canister_map, &mut names, &mut path, specific_canister)?;
                 names.into_iter().collect()
             }
-            None => canister_map.keys().cloned().collect(),
+            None => {
+                // Disabled canisters are skipped, unless an enabled canister depends on them.
+                let mut names = HashSet::new();
+                for canister_name in canister_map.keys() {
+                    if self.is_canister_enabled(canister_name)? {
+                        let mut path = vec![];
+                        add_dependencies(canister_map, &mut names, &mut path, canister_name)?;
+                    }
+                }
+                let mut names: Vec<String> = names.into_iter().collect();
+                names.sort();
+                names
+            }
         };
 
         Ok(canister_names)
     }
 
+    /// Return the names of all canisters that are disabled through their `enabled` field.
+    pub fn get_disabled_canister_names(&self) -> DfxResult<Vec<String>> {
+        let mut names = vec![];
+        if let Some(canister_map) = &self.canisters {
+            for canister_name in canister_map.keys() {
+                if !self.is_canister_enabled(canister_name)? {
+                    names.push(canister_name.clone());
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Whether a canister is enabled. The `enabled` field of a canister can either be a
+    /// boolean, or a string that is evaluated after replacing `${VAR}` with the value of
+    /// the environment variable `VAR`. Canisters are enabled by default.
+    pub fn is_canister_enabled(&self, canister_name: &str) -> DfxResult<bool> {
+        self.is_canister_enabled_with(canister_name, &|name| std::env::var(name).ok())
+    }
+
+    /// Same as `is_canister_enabled`, with the variables of the `enabled` field looked up
+    /// through `var`.
+    fn is_canister_enabled_with(
+        &self,
+        canister_name: &str,
+        var: &dyn Fn(&str) -> Option<String>,
+    ) -> DfxResult<bool> {
+        let canister_config = self
+            .canisters
+            .as_ref()
+            .and_then(|canisters| canisters.get(canister_name))
+            .ok_or_else(|| anyhow!("Cannot find canister '{}'.", canister_name))?;
+
+        match canister_config.extras.get("enabled") {
+            None => Ok(true),
+            Some(Value::Bool(enabled)) => Ok(*enabled),
+            Some(Value::String(flag)) => {
+                let flag = interpolate_env_vars(flag, var);
+                match flag.trim().to_lowercase().as_str() {
+                    "true" | "1" | "yes" | "on" => Ok(true),
+                    "false" | "0" | "no" | "off" | "" => Ok(false),
+                    other => Err(error_invalid_config!(
+                        "Field 'enabled' of canister '{}' has an invalid value '{}'.",
+                        canister_name,
+                        other
+                    )),
+                }
+            }
+            Some(_) => Err(error_invalid_config!(
+                "Field 'enabled' of canister '{}' is of the wrong type.",
+                canister_name
+            )),
+        }
+    }
+
     pub fn get_compute_allocation(&self, canister_name: &str) -> DfxResult<Option<String>> {
         self.get_initialization_value(canister_name, "compute_allocation")
     }
@@ -335,6 +496,44 @@ impl ConfigInterface {
     }
 }
 
+/// Replace every `${VAR}` in a string with the value of the variable `VAR`, as returned by
+/// `var`. Unset variables are replaced with an empty string.
+fn interpolate_env_vars(s: &str, var: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut result = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        match rest[start..].find('}') {
+            Some(len) => {
+                let name = &rest[start + 2..start + len];
+                result.push_str(&rest[..start]);
+                result.push_str(&var(name).unwrap_or_default());
+                rest = &rest[start + len + 1..];
+            }
+            None => break,
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Collect the paths of the fields of `json` that are missing from `known`, the same
+/// configuration after a round trip through `ConfigInterface`, which drops unknown fields.
+fn collect_unknown_fields(json: &Value, known: &Value, path: &str, unknown: &mut Vec<String>) {
+    if let (Value::Object(json), Value::Object(known)) = (json, known) {
+        for (key, value) in json {
+            let field = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            match known.get(key) {
+                Some(known) => collect_unknown_fields(value, known, &field, unknown),
+                None => unknown.push(field),
+            }
+        }
+    }
+}
+
 fn add_dependencies(
     all_canisters: &BTreeMap<String, ConfigCanistersCanister>,
     names: &mut HashSet<String>,
@@ -382,29 +581,42 @@ pub struct Config {
     json: Value,
     // public interface to the config:
     pub config: ConfigInterface,
+    // overrides the .dfx directory next to dfx.json, e.g. to build into a throwaway directory.
+    temp_path: Option<PathBuf>,
 }
 
 #[allow(dead_code)]
 impl Config {
+    /// Find the dfx.json of the project containing `working_dir`, searching upwards. The search
+    /// stops at the root of a git repository, or at the directory named by DFX_PROJECT_ROOT, so
+    /// that a dfx.json outside of them (e.g. of an enclosing monorepo) is never picked up.
     pub fn resolve_config_path(working_dir: &Path) -> Result<PathBuf, std::io::Error> {
+        let boundary = std::env::var_os(PROJECT_ROOT_ENV_VAR)
+            .map(PathBuf::from)
+            .map(|root| root.canonicalize().unwrap_or(root));
+        Config::resolve_config_path_within(working_dir, boundary.as_deref())
+    }
+
+    fn resolve_config_path_within(
+        working_dir: &Path,
+        boundary: Option<&Path>,
+    ) -> Result<PathBuf, std::io::Error> {
+        let not_found = || std::io::Error::new(std::io::ErrorKind::NotFound, "Config not found.");
         let mut curr = PathBuf::from(working_dir).canonicalize()?;
-        while curr.parent().is_some() {
+        if let Some(boundary) = boundary {
+            if !curr.starts_with(boundary) {
+                return Err(not_found());
+            }
+        }
+        loop {
             if curr.join(CONFIG_FILE_NAME).is_file() {
                 return Ok(curr.join(CONFIG_FILE_NAME));
-            } else {
-                curr.pop();
+            }
+            // The root (e.g. on VMs / CI) is checked too.
+            if curr.join(".git").is_dir() || Some(curr.as_path()) == boundary || !curr.pop() {
+                return Err(not_found());
             }
         }
-
-        // Have to check if the config could be in the root (e.g. on VMs / CI).
-        if curr.join(CONFIG_FILE_NAME).is_file() {
-            return Ok(curr.join(CONFIG_FILE_NAME));
-        }
-
-        Err(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "Config not found.",
-        ))
     }
 
     pub fn from_file(path: &Path) -> std::io::Result<Config> {
@@ -412,19 +624,161 @@ impl Config {
         Config::from_slice(path.to_path_buf(), &content)
     }
 
-    pub fn from_dir(working_dir: &Path) -> std::io::Result<Config> {
-        let path = Config::resolve_config_path(working_dir)?;
-        Config::from_file(&path)
+    /// Load the configuration of the project containing `working_dir`. Returns `None` if no
+    /// dfx.json exists there or in any parent directory, and an error if one exists but cannot
+    /// be loaded.
+    pub fn from_dir(working_dir: &Path) -> DfxResult<Option<Config>> {
+        let path = match Config::resolve_config_path(working_dir) {
+            Ok(path) => path,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        Config::from_path(&path).map(Some)
+    }
+
+    /// Load and validate the configuration in the dfx.json file at `path`, e.g. for tools that
+    /// point at a project explicitly instead of searching from the current directory.
+    pub fn from_path(path: &Path) -> DfxResult<Config> {
+        let config = Config::from_file(path)
+            .map_err(|err| error_invalid_data!("Failed to load '{}': {}", path.display(), err))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// The moc binary configured in `defaults.build.moc_path`, if any.
+    pub fn get_moc_path(&self) -> Option<PathBuf> {
+        let moc_path = self.config.get_defaults().get_build().moc_path.as_ref()?;
+        Some(self.get_project_root().join(moc_path))
+    }
+
+    fn check_moc_path(&self) -> DfxResult {
+        if let Some(moc_path) = self.get_moc_path() {
+            let is_executable = std::fs::metadata(&moc_path)
+                .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false);
+            if !is_executable {
+                return Err(error_invalid_config!(
+                    "Field 'defaults.build.moc_path' must name an executable file, but '{}' is not one.",
+                    moc_path.display()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the fields of the configuration that cannot be checked while deserializing it.
+    pub fn validate(&self) -> DfxResult {
+        let ledger = self.config.get_defaults().get_ledger();
+        ledger.get_ledger_canister_id()?;
+        ledger.get_cmc_canister_id()?;
+        let default_network = self.config.get_default_network();
+        if self.config.get_network(default_network).is_none() {
+            return Err(error_invalid_config!(
+                "Field 'default_network' names the network '{}', which is not in 'networks'.",
+                default_network
+            ));
+        }
+        self.check_canisters()?;
+        self.check_moc_path()?;
+        if self.config.strict == Some(true) {
+            self.check_unknown_fields()?;
+        }
+        Ok(())
+    }
+
+    /// Check the fields of the canisters that are only deserialized when the canister is
+    /// built, so that mistakes are reported with their location as soon as dfx.json is loaded.
+    fn check_canisters(&self) -> DfxResult {
+        let canisters = match &self.config.canisters {
+            Some(canisters) => canisters,
+            None => return Ok(()),
+        };
+        for (name, canister) in canisters {
+            let pointer = |field: &str| format!("/canisters/{}/{}", name, field);
+
+            if canister.r#type.as_deref().unwrap_or("motoko") == "motoko"
+                && !canister.extras.contains_key("main")
+            {
+                return Err(error_invalid_config!(
+                    "{} is missing, Motoko canisters need a main file.",
+                    pointer("main")
+                ));
+            }
+            for field in &[
+                "main",
+                "candid",
+                "wasm",
+                "package",
+                "args",
+                "init_arg",
+                "init_arg_file",
+            ] {
+                match canister.extras.get(*field) {
+                    Some(value) if !value.is_string() => {
+                        return Err(error_invalid_config!(
+                            "{} must be a string.",
+                            pointer(field)
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(dependencies) = canister.extras.get("dependencies") {
+                let dependencies = dependencies.as_array().ok_or_else(|| {
+                    error_invalid_config!("{} must be an array.", pointer("dependencies"))
+                })?;
+                for (i, dependency) in dependencies.iter().enumerate() {
+                    let pointer = pointer(&format!("dependencies/{}", i));
+                    match dependency.as_str() {
+                        Some(dependency) if canisters.contains_key(dependency) => {}
+                        Some(dependency) => {
+                            return Err(error_invalid_config!(
+                                "{} names the canister '{}', which is not in 'canisters'.",
+                                pointer,
+                                dependency
+                            ));
+                        }
+                        None => {
+                            return Err(error_invalid_config!("{} must be a string.", pointer));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail if dfx.json contains fields that dfx does not know about. Unknown fields are
+    /// ignored otherwise, so that configurations written for newer versions of dfx still load.
+    /// Canisters accept arbitrary fields, so only their known sub-objects are checked.
+    pub fn check_unknown_fields(&self) -> DfxResult {
+        let known = serde_json::to_value(&self.config)?;
+        let mut unknown = vec![];
+        collect_unknown_fields(&self.json, &known, "", &mut unknown);
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(error_invalid_config!(
+                "Unknown field(s) in '{}': {}",
+                self.path.display(),
+                unknown.join(", ")
+            ))
+        }
     }
 
-    pub fn from_current_dir() -> std::io::Result<Config> {
+    pub fn from_current_dir() -> DfxResult<Option<Config>> {
         Config::from_dir(&std::env::current_dir()?)
     }
 
     fn from_slice(path: PathBuf, content: &[u8]) -> std::io::Result<Config> {
         let config = serde_json::from_slice(&content)?;
         let json = serde_json::from_slice(&content)?;
-        Ok(Config { path, json, config })
+        Ok(Config {
+            path,
+            json,
+            config,
+            temp_path: None,
+        })
     }
 
     /// Create a configuration from a string.
@@ -441,7 +795,18 @@ impl Config {
         &self.path
     }
     pub fn get_temp_path(&self) -> PathBuf {
-        self.get_path().parent().unwrap().join(".dfx")
+        match &self.temp_path {
+            Some(temp_path) => temp_path.clone(),
+            None => self.get_path().parent().unwrap().join(".dfx"),
+        }
+    }
+    /// Return a copy of this configuration whose temporary files (including all build
+    /// outputs) are written under `temp_path` instead of the project's `.dfx` directory.
+    pub fn with_temp_path(&self, temp_path: &Path) -> Config {
+        Config {
+            temp_path: Some(temp_path.to_path_buf()),
+            ..self.clone()
+        }
     }
     pub fn get_json(&self) -> &Value {
         &self.json
@@ -453,6 +818,15 @@ impl Config {
         &self.config
     }
 
+    /// Re-read the configuration from its JSON after it was changed through `get_mut_json`,
+    /// and validate it. Fails, leaving the configuration unusable, if the change invalidated it.
+    pub fn reload_json(&mut self) -> DfxResult {
+        self.config = serde_json::from_value(self.json.clone()).map_err(|e| {
+            error_invalid_config!("Failed to load '{}': {}", self.path.display(), e)
+        })?;
+        self.validate()
+    }
+
     pub fn get_project_root(&self) -> &Path {
         // a configuration path contains a file name specifically. As
         // such we should be returning at least root as parent. If
@@ -473,6 +847,7 @@ impl Config {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn find_dfinity_config_current_path() {
@@ -519,6 +894,152 @@ mod tests {
         );
     }
 
+    #[test]
+    fn modified_json_is_reloaded_and_validated() {
+        let mut config =
+            Config::from_str(r#"{ "canisters": { "app": { "main": "main.mo" } } }"#).unwrap();
+
+        *config
+            .get_mut_json()
+            .pointer_mut("/canisters/app/main")
+            .unwrap() = Value::String("src/main.mo".to_string());
+        config.reload_json().unwrap();
+        let app = &config.get_config().canisters.as_ref().unwrap()["app"];
+        assert_eq!(app.extras["main"], "src/main.mo");
+
+        *config
+            .get_mut_json()
+            .pointer_mut("/canisters/app/main")
+            .unwrap() = Value::from(42);
+        let err = config.reload_json().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("/canisters/app/main must be a string"));
+
+        *config.get_mut_json().pointer_mut("/canisters").unwrap() = Value::from(42);
+        assert!(config.reload_json().is_err());
+    }
+
+    #[test]
+    fn config_search_stops_at_git_root() {
+        let root_dir = tempfile::tempdir().unwrap();
+        let root_path = root_dir.path().canonicalize().unwrap();
+        let repo_path = root_path.join("monorepo");
+        let project_path = repo_path.join("apps/app");
+        std::fs::create_dir_all(project_path.join("src")).unwrap();
+        std::fs::create_dir_all(repo_path.join(".git")).unwrap();
+        std::fs::write(root_path.join(CONFIG_FILE_NAME), "{}").unwrap();
+        std::fs::write(project_path.join(CONFIG_FILE_NAME), "{}").unwrap();
+
+        assert_eq!(
+            Config::resolve_config_path_within(&project_path.join("src"), None).unwrap(),
+            project_path.join(CONFIG_FILE_NAME)
+        );
+        // The dfx.json above the repository is not picked up.
+        assert!(Config::resolve_config_path_within(&repo_path.join("apps"), None).is_err());
+        assert_eq!(
+            Config::resolve_config_path_within(&root_path, None).unwrap(),
+            root_path.join(CONFIG_FILE_NAME)
+        );
+    }
+
+    #[test]
+    fn config_search_ignores_git_files() {
+        let root_dir = tempfile::tempdir().unwrap();
+        let root_path = root_dir.path().canonicalize().unwrap();
+        let project_path = root_path.join("app");
+        std::fs::create_dir_all(project_path.join("src")).unwrap();
+        std::fs::write(root_path.join(CONFIG_FILE_NAME), "{}").unwrap();
+        std::fs::write(project_path.join(".git"), "gitdir: ../.git/modules/app").unwrap();
+
+        // Only a .git directory stops the search, so it goes on above a .git file.
+        assert_eq!(
+            Config::resolve_config_path_within(&project_path.join("src"), None).unwrap(),
+            root_path.join(CONFIG_FILE_NAME)
+        );
+    }
+
+    #[test]
+    fn config_search_stops_at_project_root_boundary() {
+        let root_dir = tempfile::tempdir().unwrap();
+        let root_path = root_dir.path().canonicalize().unwrap();
+        let outer_path = root_path.join("outer");
+        let inner_path = outer_path.join("inner");
+        std::fs::create_dir_all(inner_path.join("src")).unwrap();
+        std::fs::write(outer_path.join(CONFIG_FILE_NAME), "{}").unwrap();
+
+        let work_dir = inner_path.join("src");
+        assert_eq!(
+            Config::resolve_config_path_within(&work_dir, Some(&outer_path)).unwrap(),
+            outer_path.join(CONFIG_FILE_NAME)
+        );
+        assert!(Config::resolve_config_path_within(&work_dir, Some(&inner_path)).is_err());
+
+        std::fs::write(inner_path.join(CONFIG_FILE_NAME), "{}").unwrap();
+        assert_eq!(
+            Config::resolve_config_path_within(&work_dir, Some(&inner_path)).unwrap(),
+            inner_path.join(CONFIG_FILE_NAME)
+        );
+        // A working directory outside of the boundary has no project.
+        assert!(Config::resolve_config_path_within(&root_path, Some(&inner_path)).is_err());
+    }
+
+    #[test]
+    fn moc_path_must_be_an_executable_file() {
+        let root_dir = tempfile::tempdir().unwrap();
+        let config_path = root_dir.path().join(CONFIG_FILE_NAME);
+        let config_with_moc = |moc_path: &str| {
+            let content = format!(
+                r#"{{ "defaults": {{ "build": {{ "moc_path": "{}" }} }} }}"#,
+                moc_path
+            );
+            Config::from_str_and_path(config_path.clone(), &content).unwrap()
+        };
+
+        let moc_path = root_dir.path().join("bin/moc");
+        std::fs::create_dir_all(moc_path.parent().unwrap()).unwrap();
+        std::fs::write(&moc_path, "#!/bin/sh\n").unwrap();
+        let config = config_with_moc("bin/moc");
+        assert_eq!(config.get_moc_path(), Some(moc_path.clone()));
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("must name an executable file"));
+
+        std::fs::set_permissions(&moc_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        config.validate().unwrap();
+        config_with_moc(&moc_path.display().to_string())
+            .validate()
+            .unwrap();
+
+        assert!(config_with_moc("bin").validate().is_err());
+        assert!(config_with_moc("bin/missing").validate().is_err());
+        assert_eq!(Config::from_str("{}").unwrap().get_moc_path(), None);
+    }
+
+    #[test]
+    fn config_is_loaded_from_an_explicit_path() {
+        let root_dir = tempfile::tempdir().unwrap();
+        let config_path = root_dir.path().join("custom.json");
+        std::fs::write(
+            &config_path,
+            r#"{ "canisters": { "app": { "main": "main.mo" } } }"#,
+        )
+        .unwrap();
+
+        let config = Config::from_path(&config_path).unwrap();
+        assert_eq!(config.get_path(), &config_path);
+        assert_eq!(config.get_project_root(), root_dir.path());
+        assert!(config
+            .get_config()
+            .canisters
+            .as_ref()
+            .unwrap()
+            .contains_key("app"));
+
+        std::fs::write(&config_path, r#"{ "canisters": { "app": {} } }"#).unwrap();
+        assert!(Config::from_path(&config_path).is_err());
+        assert!(Config::from_path(&root_dir.path().join(CONFIG_FILE_NAME)).is_err());
+    }
+
     #[test]
     fn config_with_local_bind_addr() {
         let config = Config::from_str(
@@ -671,6 +1192,118 @@ mod tests {
         );
     }
 
+    #[test]
+    fn canister_dependencies_are_resolved_transitively() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "app": { "dependencies": ["left", "right"] },
+                "left": { "dependencies": ["base"] },
+                "right": { "dependencies": ["base"] },
+                "base": { "dependencies": ["util"] },
+                "util": {},
+                "other": {}
+              }
+        }"#,
+        )
+        .unwrap();
+        let dependencies = |name| {
+            let mut names = config
+                .get_config()
+                .get_canister_names_with_dependencies(Some(name))
+                .unwrap();
+            names.sort();
+            names
+        };
+
+        assert_eq!(dependencies("base"), ["base", "util"]);
+        assert_eq!(
+            dependencies("app"),
+            ["app", "base", "left", "right", "util"]
+        );
+    }
+
+    #[test]
+    fn circular_canister_dependencies_are_rejected() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "app": { "dependencies": ["lib"] },
+                "lib": { "dependencies": ["util"] },
+                "util": { "dependencies": ["lib"] }
+              }
+        }"#,
+        )
+        .unwrap();
+
+        let err = config
+            .get_config()
+            .get_canister_names_with_dependencies(Some("app"))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Found circular dependency: app -> lib -> util -> lib"));
+    }
+
+    #[test]
+    fn disabled_canisters_are_skipped() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "admin": { "enabled": "off" },
+                "app": { "dependencies": ["lib"] },
+                "lib": { "enabled": false },
+                "unused": { "enabled": false }
+              }
+        }"#,
+        )
+        .unwrap();
+
+        let config_interface = config.get_config();
+        assert_eq!(
+            config_interface
+                .get_canister_names_with_dependencies(None)
+                .unwrap(),
+            vec!["app".to_string(), "lib".to_string()]
+        );
+        assert_eq!(
+            config_interface.get_disabled_canister_names().unwrap(),
+            vec!["admin".to_string(), "lib".to_string(), "unused".to_string()]
+        );
+    }
+
+    #[test]
+    fn enabled_flag_interpolates_variables() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "admin": { "enabled": "${ENABLE_ADMIN}" },
+                "app": { "enabled": "${ENABLE_APP}" }
+              }
+        }"#,
+        )
+        .unwrap();
+        let vars: HashMap<&str, &str> = vec![("ENABLE_ADMIN", "true"), ("ENABLE_APP", "maybe")]
+            .into_iter()
+            .collect();
+        let var = |name: &str| vars.get(name).map(|v| v.to_string());
+        let config_interface = config.get_config();
+
+        assert!(config_interface
+            .is_canister_enabled_with("admin", &var)
+            .unwrap());
+        assert!(!config_interface
+            .is_canister_enabled_with("admin", &|_| None)
+            .unwrap());
+        assert!(config_interface
+            .is_canister_enabled_with("app", &var)
+            .is_err());
+        assert_eq!(
+            interpolate_env_vars("a${ENABLE_ADMIN}b${UNSET}c${", &var),
+            "atruebc${"
+        );
+    }
+
     #[test]
     fn get_correct_initialization_values() {
         let config = Config::from_str(
@@ -719,4 +1352,128 @@ mod tests {
         assert_eq!(None, compute_allocation);
         assert_eq!(None, memory_allocation);
     }
+
+    #[test]
+    fn ledger_canister_ids() {
+        let config = Config::from_str("{}").unwrap();
+        let ledger = config.get_config().get_defaults().get_ledger();
+        assert_eq!(
+            ledger.get_ledger_canister_id().unwrap(),
+            Principal::from_text(LEDGER_CANISTER_ID).unwrap()
+        );
+        assert_eq!(
+            ledger.get_cmc_canister_id().unwrap(),
+            Principal::from_text(CYCLE_MINTER_CANISTER_ID).unwrap()
+        );
+
+        let config = Config::from_str(
+            r#"{
+              "defaults": {
+                "ledger": {
+                  "ledger_canister": "rrkah-fqaaa-aaaaa-aaaaq-cai",
+                  "cmc_canister": "not a principal"
+                }
+              }
+        }"#,
+        )
+        .unwrap();
+        let ledger = config.get_config().get_defaults().get_ledger();
+        assert_eq!(
+            ledger.get_ledger_canister_id().unwrap(),
+            Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap()
+        );
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected_when_strict() {
+        let config = Config::from_str(
+            r#"{
+              "canster": {},
+              "canisters": {
+                "app": { "main": "main.mo", "declarations": { "outptu": "out" } }
+              },
+              "defaults": { "build": { "packtol": "vessel" } },
+              "networks": { "local": { "bind": "127.0.0.1:8000" } }
+        }"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+
+        let err = config.check_unknown_fields().unwrap_err().to_string();
+        assert!(err.contains("canster"));
+        assert!(err.contains("canisters.app.declarations.outptu"));
+        assert!(err.contains("defaults.build.packtol"));
+        assert!(!err.contains("main"));
+        assert!(!err.contains("bind"));
+
+        let config = Config::from_str(r#"{ "strict": true, "canster": {} }"#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn canister_fields_are_checked_with_their_location() {
+        let validate = |canisters: &str| {
+            Config::from_str(&format!(r#"{{ "canisters": {} }}"#, canisters))
+                .unwrap()
+                .validate()
+                .map_err(|err| err.to_string())
+        };
+
+        assert!(validate(
+            r#"{
+              "app": { "main": "main.mo", "dependencies": ["lib"] },
+              "lib": { "type": "motoko", "main": "lib.mo" },
+              "assets": { "type": "assets", "source": ["dist"] }
+        }"#
+        )
+        .is_ok());
+
+        let err = validate(r#"{ "app": { "dependencies": [] } }"#).unwrap_err();
+        assert!(err.contains("/canisters/app/main is missing"), "{}", err);
+
+        let err = validate(r#"{ "app": { "main": 42 } }"#).unwrap_err();
+        assert!(
+            err.contains("/canisters/app/main must be a string"),
+            "{}",
+            err
+        );
+
+        let err = validate(r#"{ "app": { "type": "custom", "wasm": ["app.wasm"] } }"#).unwrap_err();
+        assert!(
+            err.contains("/canisters/app/wasm must be a string"),
+            "{}",
+            err
+        );
+
+        let err =
+            validate(r#"{ "app": { "main": "main.mo", "dependencies": ["lbi"] } }"#).unwrap_err();
+        assert!(
+            err.contains("/canisters/app/dependencies/0 names the canister 'lbi'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn default_network_must_be_configured() {
+        let config = Config::from_str("{}").unwrap();
+        assert_eq!(config.get_config().get_default_network(), "local");
+
+        let config = Config::from_str(r#"{ "default_network": "ic" }"#).unwrap();
+        assert_eq!(config.get_config().get_default_network(), "ic");
+        assert!(config.validate().is_ok());
+
+        let config = Config::from_str(
+            r#"{
+              "default_network": "staging",
+              "networks": { "staging": { "providers": [ "https://1.2.3.4:5000" ] } }
+        }"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+
+        let config = Config::from_str(r#"{ "default_network": "stagign" }"#).unwrap();
+        assert!(config.validate().is_err());
+    }
 }
@@ -8,6 +8,7 @@ use crate::lib::canister_info::assets::AssetsCanisterInfo;
 use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
 use crate::lib::error::{BuildError, DfxError, DfxResult};
+use crate::lib::ignore::IgnoreMatcher;
 use crate::lib::models::canister::CanisterPool;
 use crate::util;
 
@@ -108,6 +109,11 @@ impl CanisterBuilder for AssetsBuilder {
         info: &CanisterInfo,
         config: &BuildConfig,
     ) -> DfxResult {
+        // The assets are built by the frontend, so there is nothing to copy without it.
+        if config.skip_frontend {
+            return Ok(());
+        }
+
         let deps = match info.get_extra_value("dependencies") {
             None => vec![],
             Some(v) => Vec::<String>::deserialize(v)
@@ -136,7 +142,13 @@ impl CanisterBuilder for AssetsBuilder {
         let assets_canister_info = info.as_info::<AssetsCanisterInfo>()?;
         assets_canister_info.assert_source_paths()?;
 
-        copy_assets(pool.get_logger(), &assets_canister_info)?;
+        let ignore = IgnoreMatcher::from_project_root(info.get_workspace_root())?;
+        copy_assets(
+            pool.get_logger(),
+            info.get_workspace_root(),
+            &ignore,
+            &assets_canister_info,
+        )?;
         Ok(())
     }
 
@@ -212,7 +224,14 @@ fn delete_output_directory(
     Ok(())
 }
 
-fn copy_assets(logger: &slog::Logger, assets_canister_info: &AssetsCanisterInfo) -> DfxResult {
+/// Copy the asset sources to the output directory, skipping hidden files and paths
+/// matched by the project's `.dfxignore`.
+fn copy_assets(
+    logger: &slog::Logger,
+    project_root: &Path,
+    ignore: &IgnoreMatcher,
+    assets_canister_info: &AssetsCanisterInfo,
+) -> DfxResult {
     let source_paths = assets_canister_info.get_source_paths();
     let output_assets_path = assets_canister_info.get_output_assets_path();
 
@@ -230,7 +249,13 @@ fn copy_assets(logger: &slog::Logger, assets_canister_info: &AssetsCanisterInfo)
 
         let input_assets_path = source_path.as_path();
         let walker = WalkDir::new(input_assets_path).into_iter();
-        for entry in walker.filter_entry(|e| !is_hidden(e)) {
+        let is_ignored = |e: &walkdir::DirEntry| {
+            let path = project_root.join(e.path());
+            path.strip_prefix(project_root).map_or(false, |relative| {
+                ignore.is_ignored(relative, e.file_type().is_dir())
+            })
+        };
+        for entry in walker.filter_entry(|e| !is_hidden(e) && !is_ignored(e)) {
             let entry = entry?;
             let source = entry.path();
             let relative = source
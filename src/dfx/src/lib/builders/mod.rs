@@ -7,10 +7,10 @@ use crate::lib::models::canister::CanisterPool;
 use crate::lib::provider::get_network_context;
 use crate::util::check_candid_file;
 
-use anyhow::{bail, Context};
+use anyhow::{anyhow, bail, Context};
 use ic_types::principal::Principal as CanisterId;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 mod assets;
@@ -18,6 +18,8 @@ mod custom;
 mod motoko;
 mod rust;
 
+pub use motoko::compile_standalone;
+
 #[derive(Debug)]
 pub enum WasmBuildOutput {
     // Wasm(Vec<u8>),
@@ -126,6 +128,9 @@ pub trait CanisterBuilder {
             );
         }
 
+        // Files to pass to the formatter of the project, if any.
+        let mut js_paths = vec![];
+
         // Typescript
         if bindings.contains(&"ts".to_string()) {
             let output_did_ts_path = generate_output_dir
@@ -134,6 +139,7 @@ pub trait CanisterBuilder {
             let content = ensure_trailing_newline(candid::bindings::typescript::compile(&env, &ty));
             std::fs::write(&output_did_ts_path, content)?;
             eprintln!("  {}", &output_did_ts_path.display());
+            js_paths.push(output_did_ts_path);
         }
 
         // Javascript
@@ -145,6 +151,7 @@ pub trait CanisterBuilder {
             let content = ensure_trailing_newline(candid::bindings::javascript::compile(&env, &ty));
             std::fs::write(&output_did_js_path, content)?;
             eprintln!("  {}", &output_did_js_path.display());
+            js_paths.push(output_did_js_path);
 
             // index.js
             let mut language_bindings = crate::util::assets::language_bindings()?;
@@ -167,8 +174,10 @@ pub trait CanisterBuilder {
                 let index_js_path = generate_output_dir.join("index").with_extension("js");
                 std::fs::write(&index_js_path, new_file_contents)?;
                 eprintln!("  {}", &index_js_path.display());
+                js_paths.push(index_js_path);
             }
         }
+        format_js_files(info, &js_paths)?;
 
         // Motoko
         if bindings.contains(&"mo".to_string()) {
@@ -199,6 +208,41 @@ pub trait CanisterBuilder {
     }
 }
 
+/// Run the formatter configured in `defaults.build.js_format` on generated JavaScript and
+/// TypeScript files. Does nothing if no formatter is configured.
+pub fn format_js_files(info: &CanisterInfo, paths: &[PathBuf]) -> DfxResult {
+    match info.get_js_format() {
+        Some(js_format) if !paths.is_empty() => {
+            run_js_format(js_format, info.get_workspace_root(), paths)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Run the `js_format` command line in `workspace_root`, with `paths` appended to its arguments.
+fn run_js_format(js_format: &str, workspace_root: &Path, paths: &[PathBuf]) -> DfxResult {
+    let args = shell_words::split(js_format)
+        .with_context(|| format!("Cannot parse js_format command '{}'.", js_format))?;
+    let (program, args) = args
+        .split_first()
+        .context("`js_format` must not be empty")?;
+    let output = std::process::Command::new(program)
+        .args(args)
+        .args(paths)
+        .current_dir(workspace_root)
+        .output()
+        .with_context(|| format!("Failed to run js_format command '{}'.", js_format))?;
+    if !output.status.success() {
+        bail!(
+            "The js_format command '{}' failed with {}:\n{}",
+            js_format,
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
 // TODO: this function was copied from src/lib/models/canister.rs
 fn ensure_trailing_newline(s: String) -> String {
     if s.ends_with('\n') {
@@ -210,10 +254,61 @@ fn ensure_trailing_newline(s: String) -> String {
     }
 }
 
+/// Fail with a targeted error if the source file of a canister does not exist. A source inside
+/// a git submodule that was never checked out is reported as such, rather than as a missing file.
+pub fn check_source_exists(canister_name: &str, path: &Path) -> DfxResult {
+    if path.exists() {
+        return Ok(());
+    }
+    match find_uninitialized_submodule(path) {
+        Some(submodule) => Err(anyhow!(
+            "The source '{}' of canister '{}' is in the git submodule '{}', which is not \
+             initialized. Run 'git submodule update --init'.",
+            path.display(),
+            canister_name,
+            submodule.display()
+        )),
+        None => Err(anyhow!(
+            "The source '{}' of canister '{}' does not exist.",
+            path.display(),
+            canister_name
+        )),
+    }
+}
+
+/// Return the git submodule containing `path` if it is declared in a `.gitmodules` file of one
+/// of the parent directories but not checked out, i.e. its directory has no `.git` entry.
+fn find_uninitialized_submodule(path: &Path) -> Option<PathBuf> {
+    for dir in path.ancestors().skip(1) {
+        let gitmodules = match std::fs::read_to_string(dir.join(".gitmodules")) {
+            Ok(gitmodules) => gitmodules,
+            Err(_) => continue,
+        };
+        for line in gitmodules.lines() {
+            let submodule = match line.trim().strip_prefix("path") {
+                Some(rest) => match rest.trim_start().strip_prefix('=') {
+                    Some(submodule) => dir.join(submodule.trim()),
+                    None => continue,
+                },
+                None => continue,
+            };
+            if path.starts_with(&submodule) && !submodule.join(".git").exists() {
+                return Some(submodule);
+            }
+        }
+    }
+    None
+}
+
 #[derive(Clone)]
 pub struct BuildConfig {
     profile: Profile,
     pub build_mode_check: bool,
+    /// Whether to skip building the frontend of asset canisters, which writes outside the
+    /// build root.
+    pub skip_frontend: bool,
+    /// Whether to record the git commit of the project in the built wasm modules.
+    pub embed_git: bool,
     pub network_name: String,
 
     /// The root of all IDL files.
@@ -233,6 +328,8 @@ impl BuildConfig {
             network_name,
             profile: config_intf.profile.unwrap_or(Profile::Debug),
             build_mode_check: false,
+            skip_frontend: false,
+            embed_git: config_intf.get_defaults().get_build().get_embed_git(),
             build_root: build_root.clone(),
             idl_root: build_root.join("idl/"),
         })
@@ -244,6 +341,22 @@ impl BuildConfig {
             ..self
         }
     }
+
+    /// Overrides the profile configured in dfx.json.
+    pub fn with_profile(self, profile: Profile) -> Self {
+        Self { profile, ..self }
+    }
+
+    pub fn get_profile(&self) -> Profile {
+        self.profile
+    }
+
+    pub fn with_skip_frontend(self, skip_frontend: bool) -> Self {
+        Self {
+            skip_frontend,
+            ..self
+        }
+    }
 }
 
 pub struct BuilderPool {
@@ -269,3 +382,67 @@ impl BuilderPool {
             .map(|x| Arc::clone(x))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_source_in_uninitialized_submodule() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        std::fs::write(
+            root.join(".gitmodules"),
+            "[submodule \"vendor/lib\"]\n\tpath = vendor/lib\n\turl = https://example.com/lib.git\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("vendor/lib")).unwrap();
+        let main = root.join("vendor/lib/src/main.mo");
+
+        let err = check_source_exists("app", &main).unwrap_err().to_string();
+        assert!(err.contains("git submodule update"));
+
+        std::fs::write(
+            root.join("vendor/lib/.git"),
+            "gitdir: ../../.git/modules/lib",
+        )
+        .unwrap();
+        let err = check_source_exists("app", &main).unwrap_err().to_string();
+        assert!(err.contains("does not exist"));
+
+        std::fs::create_dir_all(main.parent().unwrap()).unwrap();
+        std::fs::write(&main, "actor {}").unwrap();
+        assert!(check_source_exists("app", &main).is_ok());
+    }
+
+    #[test]
+    fn js_format_keeps_quoted_arguments() {
+        let root = tempfile::tempdir().unwrap();
+        let paths = vec![root.path().join("a.js"), root.path().join("b d.ts")];
+
+        run_js_format(
+            "sh -c 'for f; do echo \"formatted in $(pwd)\" >\"$f\"; done' sh",
+            root.path(),
+            &paths,
+        )
+        .unwrap();
+        let expected = format!(
+            "formatted in {}\n",
+            root.path().canonicalize().unwrap().display()
+        );
+        for path in &paths {
+            assert_eq!(std::fs::read_to_string(path).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn js_format_failures_are_reported() {
+        let root = tempfile::tempdir().unwrap();
+        let paths = vec![root.path().join("a.js")];
+
+        let err = run_js_format("sh -c 'exit 3'", root.path(), &paths).unwrap_err();
+        assert!(err.to_string().contains("failed"));
+        assert!(run_js_format("", root.path(), &paths).is_err());
+        assert!(run_js_format("'unterminated", root.path(), &paths).is_err());
+    }
+}
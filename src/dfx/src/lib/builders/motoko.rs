@@ -1,7 +1,7 @@
-use crate::config::cache::Cache;
+use crate::config::cache::{isolate_command, Cache};
 use crate::config::dfinity::Profile;
 use crate::lib::builders::{
-    BuildConfig, BuildOutput, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
+    check_source_exists, BuildConfig, BuildOutput, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
 };
 use crate::lib::canister_info::motoko::MotokoCanisterInfo;
 use crate::lib::canister_info::CanisterInfo;
@@ -15,13 +15,19 @@ use ic_types::principal::Principal as CanisterId;
 use slog::{info, o, trace, warn, Logger};
 use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryFrom;
+use std::os::unix::process::ExitStatusExt;
 use std::path::{Path, PathBuf};
-use std::process::Output;
+use std::process::{Command, Output};
 use std::sync::Arc;
 
 pub struct MotokoBuilder {
     logger: slog::Logger,
     cache: Arc<dyn Cache>,
+    /// The moc binary configured in dfx.json, used instead of the one in the cache.
+    moc_path: Option<PathBuf>,
+    /// The working directory to run moc in with a clean environment, if
+    /// `defaults.build.clean_env` is set.
+    clean_env_dir: Option<PathBuf>,
 }
 
 impl MotokoBuilder {
@@ -31,8 +37,30 @@ impl MotokoBuilder {
                 "module" => "motoko"
             }),
             cache: env.get_cache(),
+            moc_path: env.get_config().and_then(|config| config.get_moc_path()),
+            clean_env_dir: env
+                .get_config()
+                .filter(|config| {
+                    config
+                        .get_config()
+                        .get_defaults()
+                        .get_build()
+                        .get_clean_env()
+                })
+                .map(|config| config.get_project_root().to_path_buf()),
         })
     }
+
+    fn moc_command(&self) -> DfxResult<Command> {
+        let mut cmd = match &self.moc_path {
+            Some(moc_path) => Command::new(moc_path),
+            None => self.cache.get_binary_command("moc")?,
+        };
+        if let Some(dir) = &self.clean_env_dir {
+            isolate_command(&mut cmd, dir);
+        }
+        Ok(cmd)
+    }
 }
 
 impl CanisterBuilder for MotokoBuilder {
@@ -43,9 +71,10 @@ impl CanisterBuilder for MotokoBuilder {
     ) -> DfxResult<Vec<CanisterId>> {
         let mut result = BTreeSet::new();
         let motoko_info = info.as_info::<MotokoCanisterInfo>()?;
+        check_source_exists(info.get_name(), motoko_info.get_main_path())?;
 
         fn find_deps_recursive(
-            cache: &dyn Cache,
+            builder: &MotokoBuilder,
             file: &Path,
             result: &mut BTreeSet<MotokoImport>,
         ) -> DfxResult {
@@ -53,8 +82,8 @@ impl CanisterBuilder for MotokoBuilder {
                 return Ok(());
             }
 
-            let output = cache
-                .get_binary_command("moc")?
+            let output = builder
+                .moc_command()?
                 .arg("--print-deps")
                 .arg(&file)
                 .output()?;
@@ -67,7 +96,7 @@ impl CanisterBuilder for MotokoBuilder {
                         result.insert(import);
                     }
                     MotokoImport::Relative(path) => {
-                        find_deps_recursive(cache, path.as_path(), result)?;
+                        find_deps_recursive(builder, path.as_path(), result)?;
                     }
                     MotokoImport::Lib(_) => (),
                     MotokoImport::Ic(_) => (),
@@ -76,11 +105,7 @@ impl CanisterBuilder for MotokoBuilder {
 
             Ok(())
         }
-        find_deps_recursive(
-            self.cache.as_ref(),
-            motoko_info.get_main_path(),
-            &mut result,
-        )?;
+        find_deps_recursive(self, motoko_info.get_main_path(), &mut result)?;
 
         Ok(result
             .iter()
@@ -108,6 +133,7 @@ impl CanisterBuilder for MotokoBuilder {
         let motoko_info = canister_info.as_info::<MotokoCanisterInfo>()?;
         let profile = config.profile;
         let input_path = motoko_info.get_main_path();
+        check_source_exists(canister_info.get_name(), input_path)?;
         let output_wasm_path = motoko_info.get_output_wasm_path();
 
         let id_map = pool
@@ -124,14 +150,11 @@ impl CanisterBuilder for MotokoBuilder {
         let package_arguments =
             package_arguments::load(cache.as_ref(), motoko_info.get_packtool())?;
 
-        let moc_arguments = match motoko_info.get_args() {
-            Some(args) => [
-                package_arguments,
-                args.split_whitespace().map(str::to_string).collect(),
-            ]
-            .concat(),
-            None => package_arguments,
-        };
+        let moc_arguments = get_moc_arguments(
+            canister_info.get_name(),
+            package_arguments,
+            motoko_info.get_args(),
+        )?;
 
         // Generate IDL
         let output_idl_path = motoko_info.get_output_idl_path();
@@ -144,14 +167,11 @@ impl CanisterBuilder for MotokoBuilder {
             idl_path: &idl_dir_path,
             idl_map: &id_map,
         };
-        motoko_compile(&self.logger, cache.as_ref(), &params)?;
+        motoko_compile(&self.logger, self.moc_command()?, &params)?;
 
         // Generate wasm
         let params = MotokoParams {
-            build_target: match profile {
-                Profile::Release => BuildTarget::Release,
-                _ => BuildTarget::Debug,
-            },
+            build_target: BuildTarget::from(profile),
             // Suppress the warnings the second time we call moc
             suppress_warning: true,
             input: &input_path,
@@ -160,7 +180,7 @@ impl CanisterBuilder for MotokoBuilder {
             idl_path: &idl_dir_path,
             idl_map: &id_map,
         };
-        motoko_compile(&self.logger, cache.as_ref(), &params)?;
+        motoko_compile(&self.logger, self.moc_command()?, &params)?;
 
         Ok(BuildOutput {
             canister_id: canister_info
@@ -200,14 +220,8 @@ impl CanisterBuilder for MotokoBuilder {
         let package_arguments =
             package_arguments::load(cache.as_ref(), motoko_info.get_packtool())?;
 
-        let moc_arguments = match motoko_info.get_args() {
-            Some(args) => [
-                package_arguments,
-                args.split_whitespace().map(str::to_string).collect(),
-            ]
-            .concat(),
-            None => package_arguments,
-        };
+        let moc_arguments =
+            get_moc_arguments(info.get_name(), package_arguments, motoko_info.get_args())?;
 
         // Generate IDL
         let output_idl_path = generate_output_dir
@@ -222,7 +236,7 @@ impl CanisterBuilder for MotokoBuilder {
             idl_path: &idl_dir_path,
             idl_map: &id_map,
         };
-        motoko_compile(&self.logger, cache.as_ref(), &params)?;
+        motoko_compile(&self.logger, self.moc_command()?, &params)?;
 
         Ok(output_idl_path)
     }
@@ -230,12 +244,33 @@ impl CanisterBuilder for MotokoBuilder {
 
 type CanisterIdMap = BTreeMap<String, String>;
 
+#[derive(Debug, PartialEq)]
 enum BuildTarget {
     Release,
     Debug,
     Idl,
 }
 
+impl From<Profile> for BuildTarget {
+    fn from(profile: Profile) -> Self {
+        match profile {
+            Profile::Release => BuildTarget::Release,
+            Profile::Debug => BuildTarget::Debug,
+        }
+    }
+}
+
+impl BuildTarget {
+    /// The moc flags selecting what to compile, and how.
+    fn args(&self) -> &'static [&'static str] {
+        match self {
+            BuildTarget::Release => &["-c", "--release"],
+            BuildTarget::Debug => &["-c", "--debug"],
+            BuildTarget::Idl => &["--idl"],
+        }
+    }
+}
+
 struct MotokoParams<'a> {
     build_target: BuildTarget,
     idl_path: &'a Path,
@@ -248,14 +283,10 @@ struct MotokoParams<'a> {
 }
 
 impl MotokoParams<'_> {
-    fn to_args(&self, cmd: &mut std::process::Command) {
+    fn to_args(&self, cmd: &mut Command) {
         cmd.arg(self.input);
         cmd.arg("-o").arg(self.output);
-        match self.build_target {
-            BuildTarget::Release => cmd.args(&["-c", "--release"]),
-            BuildTarget::Debug => cmd.args(&["-c", "--debug"]),
-            BuildTarget::Idl => cmd.arg("--idl"),
-        };
+        cmd.args(self.build_target.args());
         if !self.idl_map.is_empty() {
             cmd.arg("--actor-idl").arg(self.idl_path);
             for (name, canister_id) in self.idl_map.iter() {
@@ -266,11 +297,68 @@ impl MotokoParams<'_> {
     }
 }
 
-/// Compile a motoko file.
-fn motoko_compile(logger: &Logger, cache: &dyn Cache, params: &MotokoParams<'_>) -> DfxResult {
-    let mut cmd = cache.get_binary_command("moc")?;
+/// Arguments set by dfx itself for every compilation, which the args of a canister must not
+/// override.
+const RESERVED_MOC_ARGUMENTS: &[&str] = &["-o", "-c", "--idl"];
+
+/// The arguments passed to moc after the ones set by dfx: the package arguments, followed by the
+/// args of the canister.
+fn get_moc_arguments(
+    canister_name: &str,
+    package_arguments: PackageArguments,
+    args: &Option<String>,
+) -> DfxResult<PackageArguments> {
+    let args: Vec<String> = match args {
+        Some(args) => args.split_whitespace().map(str::to_string).collect(),
+        None => return Ok(package_arguments),
+    };
+    if let Some(arg) = args
+        .iter()
+        .find(|arg| RESERVED_MOC_ARGUMENTS.contains(&arg.as_str()))
+    {
+        return Err(DfxError::new(BuildError::ReservedMocArgument(
+            canister_name.to_string(),
+            arg.clone(),
+        )));
+    }
+    Ok([package_arguments, args].concat())
+}
+
+/// Compile a motoko file, returning the output of the compiler for callers that want its
+/// diagnostics.
+fn motoko_compile(
+    logger: &Logger,
+    mut cmd: Command,
+    params: &MotokoParams<'_>,
+) -> DfxResult<Output> {
     params.to_args(&mut cmd);
-    run_command(logger, &mut cmd, params.suppress_warning)?;
+    run_command(logger, &mut cmd, params.suppress_warning)
+}
+
+/// Compile a motoko file that imports nothing into a debug wasm module and a candid
+/// interface, with the moc binary of `cache`.
+pub fn compile_standalone(
+    logger: &Logger,
+    cache: &dyn Cache,
+    input: &Path,
+    wasm_path: &Path,
+    idl_path: &Path,
+) -> DfxResult {
+    for (build_target, output) in vec![
+        (BuildTarget::Debug, wasm_path),
+        (BuildTarget::Idl, idl_path),
+    ] {
+        let params = MotokoParams {
+            build_target,
+            suppress_warning: false,
+            input,
+            package_arguments: &PackageArguments::new(),
+            output,
+            idl_path: Path::new(""),
+            idl_map: &CanisterIdMap::new(),
+        };
+        motoko_compile(logger, cache.get_binary_command("moc")?, &params)?;
+    }
     Ok(())
 }
 
@@ -346,13 +434,20 @@ impl TryFrom<&str> for MotokoImport {
 
 fn run_command(
     logger: &slog::Logger,
-    cmd: &mut std::process::Command,
+    cmd: &mut Command,
     suppress_warning: bool,
 ) -> DfxResult<Output> {
     trace!(logger, r#"Running {}..."#, format!("{:?}", cmd));
 
     let output = cmd.output()?;
-    if !output.status.success() {
+    if let Some(signal) = output.status.signal() {
+        // The compiler was killed, e.g. by a segfault, rather than rejecting the code.
+        Err(DfxError::new(BuildError::CompilerCrashed(
+            format!("{:?}", cmd),
+            signal,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )))
+    } else if !output.status.success() {
         Err(DfxError::new(BuildError::CommandError(
             format!("{:?}", cmd),
             output.status,
@@ -369,3 +464,126 @@ fn run_command(
         Ok(output)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::cache::DiskBasedCache;
+    use crate::config::dfx_version;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn run_script(script: &str) -> DfxResult<Output> {
+        let logger = Logger::root(slog::Discard, o!());
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg(script);
+        run_command(&logger, &mut cmd, false)
+    }
+
+    #[test]
+    fn compile_returns_captured_output() {
+        let logger = Logger::root(slog::Discard, o!());
+        let mut moc = std::process::Command::new("sh");
+        moc.arg("-c")
+            .arg("echo \"$@\"; echo 'warning: unused' >&2")
+            .arg("moc");
+        let params = MotokoParams {
+            build_target: BuildTarget::Debug,
+            suppress_warning: false,
+            input: Path::new("main.mo"),
+            package_arguments: &vec!["--package".to_string(), "base".to_string()],
+            output: Path::new("main.wasm"),
+            idl_path: Path::new("idl"),
+            idl_map: &CanisterIdMap::new(),
+        };
+
+        let output = motoko_compile(&logger, moc, &params).unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            output.stdout,
+            b"main.mo -o main.wasm -c --debug --package base\n".to_vec()
+        );
+        assert_eq!(output.stderr, b"warning: unused\n");
+    }
+
+    #[test]
+    fn compiler_crash_is_reported_as_crash() {
+        let err = run_script("echo boom >&2; kill -SEGV $$").unwrap_err();
+        match err.downcast_ref::<BuildError>() {
+            Some(BuildError::CompilerCrashed(_, signal, stderr)) => {
+                assert_eq!(*signal, 11);
+                assert_eq!(stderr, "boom\n");
+            }
+            _ => panic!("expected a compiler crash, got: {}", err),
+        }
+    }
+
+    #[test]
+    fn configured_moc_is_used_instead_of_the_cached_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let moc_path = dir.path().join("moc");
+        std::fs::write(&moc_path, "#!/bin/sh\necho \"custom moc $*\"\n").unwrap();
+        std::fs::set_permissions(&moc_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let builder = MotokoBuilder {
+            logger: Logger::root(slog::Discard, o!()),
+            cache: Arc::new(DiskBasedCache::with_version(dfx_version())),
+            moc_path: Some(moc_path),
+            clean_env_dir: None,
+        };
+        let output = builder
+            .moc_command()
+            .unwrap()
+            .arg("--print-deps")
+            .output()
+            .unwrap();
+        assert_eq!(output.stdout, b"custom moc --print-deps\n");
+    }
+
+    #[test]
+    fn profiles_select_the_moc_flags() {
+        assert_eq!(BuildTarget::from(Profile::Release), BuildTarget::Release);
+        assert_eq!(BuildTarget::from(Profile::Debug), BuildTarget::Debug);
+        assert_eq!(BuildTarget::Release.args(), ["-c", "--release"]);
+        assert_eq!(BuildTarget::Debug.args(), ["-c", "--debug"]);
+        assert_eq!(BuildTarget::Idl.args(), ["--idl"]);
+    }
+
+    #[test]
+    fn canister_args_follow_package_arguments() {
+        let package_arguments = vec!["--package".to_string(), "base".to_string()];
+        let args = Some("--compacting-gc  -v".to_string());
+        assert_eq!(
+            get_moc_arguments("app", package_arguments.clone(), &args).unwrap(),
+            vec!["--package", "base", "--compacting-gc", "-v"]
+        );
+        assert_eq!(
+            get_moc_arguments("app", package_arguments, &None).unwrap(),
+            vec!["--package", "base"]
+        );
+    }
+
+    #[test]
+    fn canister_args_cannot_override_reserved_arguments() {
+        let args = Some("-v -o out.wasm".to_string());
+        let err = get_moc_arguments("app", vec![], &args).unwrap_err();
+        match err.downcast_ref::<BuildError>() {
+            Some(BuildError::ReservedMocArgument(canister, arg)) => {
+                assert_eq!(canister, "app");
+                assert_eq!(arg, "-o");
+            }
+            _ => panic!("expected a reserved argument error, got: {}", err),
+        }
+    }
+
+    #[test]
+    fn compile_error_reports_stderr() {
+        let err = run_script("echo 'type error' >&2; exit 1").unwrap_err();
+        match err.downcast_ref::<BuildError>() {
+            Some(BuildError::CommandError(_, status, _, stderr)) => {
+                assert_eq!(status.code(), Some(1));
+                assert_eq!(stderr, "type error\n");
+            }
+            _ => panic!("expected a compile error, got: {}", err),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::lib::error::DfxResult;
+use crate::{error_invalid_argument, error_invalid_data};
+
+use candid::parser::typing::TypeEnv;
+use candid::types::{Function, Type};
+use candid::IDLArgs;
+
+/// Return the type of a method of a service, as found in a candid file.
+pub fn get_method_type(env: &TypeEnv, service: &Type, method: &str) -> DfxResult<Function> {
+    env.get_method(service, method)
+        .map(Function::clone)
+        .map_err(|e| error_invalid_argument!("Cannot find method '{}': {}", method, e))
+}
+
+/// Encode the textual candid arguments of a call to `method` of a service.
+pub fn encode_args(
+    env: &TypeEnv,
+    service: &Type,
+    method: &str,
+    textual: &str,
+) -> DfxResult<Vec<u8>> {
+    let func = get_method_type(env, service, method)?;
+    let args = candid::pretty_parse::<IDLArgs>("Candid argument", textual)
+        .map_err(|e| error_invalid_argument!("Invalid Candid values: {}", e))?;
+    encode_parsed_args(env, &func.args, &args)
+}
+
+/// Encode parsed candid arguments against the expected argument types, failing with the
+/// position of the first argument that does not match its type.
+pub fn encode_parsed_args(env: &TypeEnv, types: &[Type], args: &IDLArgs) -> DfxResult<Vec<u8>> {
+    for (position, (value, ty)) in args.args.iter().zip(types).enumerate() {
+        value.annotate_type(true, env, ty).map_err(|e| {
+            error_invalid_argument!(
+                "Argument {} does not match type {}: {}",
+                position + 1,
+                ty,
+                e
+            )
+        })?;
+    }
+    args.to_bytes_with_types(env, types)
+        .map_err(|e| error_invalid_data!("Unable to serialize Candid values: {}", e))
+}
+
+/// Decode the result of a call to `method` of a service.
+pub fn decode_result(
+    env: &TypeEnv,
+    service: &Type,
+    method: &str,
+    bytes: &[u8],
+) -> DfxResult<IDLArgs> {
+    let func = get_method_type(env, service, method)?;
+    IDLArgs::from_bytes_with_types(bytes, env, &func.rets)
+        .map_err(|e| error_invalid_data!("Unable to deserialize Candid values: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::parser::types::IDLProg;
+    use candid::parser::typing::check_prog;
+
+    fn service() -> (TypeEnv, Type) {
+        let prog: IDLProg = "service : { greet : (text, nat) -> (text) }"
+            .parse()
+            .unwrap();
+        let mut env = TypeEnv::new();
+        let service = check_prog(&mut env, &prog).unwrap().unwrap();
+        (env, service)
+    }
+
+    #[test]
+    fn encodes_and_decodes() {
+        let (env, service) = service();
+        let bytes = encode_args(&env, &service, "greet", r#"("world", 42)"#).unwrap();
+        let args = IDLArgs::from_bytes(&bytes).unwrap();
+        assert_eq!(args.args.len(), 2);
+
+        let result = IDLArgs::new(&[candid::parser::value::IDLValue::Text("hi".to_string())]);
+        let bytes = result.to_bytes().unwrap();
+        let decoded = decode_result(&env, &service, "greet", &bytes).unwrap();
+        assert_eq!(decoded.to_string(), result.to_string());
+    }
+
+    #[test]
+    fn reports_the_position_of_a_mismatched_argument() {
+        let (env, service) = service();
+        let err = encode_args(&env, &service, "greet", r#"("world", "42")"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Argument 2 does not match type nat"));
+    }
+
+    #[test]
+    fn reports_unknown_methods() {
+        let (env, service) = service();
+        let err = encode_args(&env, &service, "hello", "()").unwrap_err();
+        assert!(err.to_string().contains("Cannot find method 'hello'"));
+    }
+}
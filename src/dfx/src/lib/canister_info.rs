@@ -6,7 +6,7 @@ use crate::lib::canister_info::motoko::MotokoCanisterInfo;
 use crate::lib::error::DfxResult;
 use crate::lib::provider::get_network_context;
 
-use anyhow::{anyhow, bail};
+use anyhow::{anyhow, bail, Context};
 use ic_types::principal::Principal as CanisterId;
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
@@ -44,6 +44,8 @@ pub struct CanisterInfo {
 
     packtool: Option<String>,
     args: Option<String>,
+    js_format: Option<String>,
+    wasm_name: Option<String>,
 
     extras: BTreeMap<String, serde_json::Value>,
 }
@@ -107,9 +109,30 @@ impl CanisterInfo {
 
             packtool: build_defaults.get_packtool(),
             args: build_defaults.get_args(),
+            js_format: build_defaults.get_js_format(),
+            wasm_name: None,
             extras,
         };
 
+        let wasm_name: Option<String> = canister_info.get_extra_optional("wasm_name")?;
+        if let Some(wasm_name) = &wasm_name {
+            if wasm_name.is_empty()
+                || wasm_name == "."
+                || wasm_name == ".."
+                || wasm_name.contains(|c: char| c == '/' || c == '\\')
+            {
+                bail!(
+                    "Field 'wasm_name' of canister '{}' must be a file name without path separators, found '{}'.",
+                    name,
+                    wasm_name
+                );
+            }
+        }
+        let canister_info = CanisterInfo {
+            wasm_name,
+            ..canister_info
+        };
+
         let canister_args: Option<String> = canister_info.get_extra_optional("args")?;
 
         Ok(match canister_args {
@@ -122,6 +145,32 @@ impl CanisterInfo {
         })
     }
 
+    /// Create the info of a canister of the given name and extras in `workspace_root`, without
+    /// loading a dfx.json.
+    #[cfg(test)]
+    pub fn for_test(name: &str, workspace_root: &Path, extras: serde_json::Value) -> CanisterInfo {
+        let build_root = workspace_root.join(".dfx").join("local").join("canisters");
+        let extras: BTreeMap<String, serde_json::Value> = serde_json::from_value(extras).unwrap();
+        CanisterInfo {
+            name: name.to_string(),
+            canister_type: "motoko".to_string(),
+            declarations_config: CanisterDeclarationsConfig::default(),
+            workspace_root: workspace_root.to_path_buf(),
+            output_root: build_root.join(name),
+            build_root,
+            canister_root: workspace_root.to_path_buf(),
+            canister_id: None,
+            packtool: None,
+            args: None,
+            js_format: None,
+            wasm_name: extras
+                .get("wasm_name")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            extras,
+        }
+    }
+
     pub fn get_name(&self) -> &str {
         self.name.as_str()
     }
@@ -198,11 +247,38 @@ impl CanisterInfo {
         &self.args
     }
 
+    pub fn get_js_format(&self) -> &Option<String> {
+        &self.js_format
+    }
+
+    /// Return the init argument configured for this canister, either inline in `init_arg`
+    /// or in the file referenced by `init_arg_file` (relative to the project root).
+    pub fn get_init_arg(&self) -> DfxResult<Option<String>> {
+        let init_arg: Option<String> = self.get_extra_optional("init_arg")?;
+        let init_arg_file: Option<PathBuf> = self.get_extra_optional("init_arg_file")?;
+        match (init_arg, init_arg_file) {
+            (Some(_), Some(_)) => bail!(
+                "Canister '{}' cannot specify both 'init_arg' and 'init_arg_file'.",
+                self.get_name()
+            ),
+            (Some(init_arg), None) => Ok(Some(init_arg)),
+            (None, Some(init_arg_file)) => {
+                let path = self.workspace_root.join(init_arg_file);
+                std::fs::read_to_string(&path)
+                    .with_context(|| {
+                        format!("Cannot read init argument file '{}'.", path.display())
+                    })
+                    .map(Some)
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
     pub fn get_build_wasm_path(&self) -> PathBuf {
+        let file_stem = self.wasm_name.as_ref().unwrap_or(&self.name);
         self.build_root
             .join(PathBuf::from(&self.name))
-            .join(&self.name)
-            .with_extension("wasm")
+            .join(format!("{}.wasm", file_stem))
     }
 
     pub fn get_build_idl_path(&self) -> PathBuf {
@@ -258,3 +334,65 @@ impl CanisterInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_wasm_path_uses_wasm_name() {
+        let root = Path::new("/project");
+        let build_dir = root.join(".dfx/local/canisters/app");
+
+        let info = CanisterInfo::for_test("app", root, serde_json::json!({}));
+        assert_eq!(info.get_build_wasm_path(), build_dir.join("app.wasm"));
+
+        let info =
+            CanisterInfo::for_test("app", root, serde_json::json!({ "wasm_name": "backend" }));
+        assert_eq!(info.get_build_wasm_path(), build_dir.join("backend.wasm"));
+
+        let info =
+            CanisterInfo::for_test("app", root, serde_json::json!({ "wasm_name": "my.app" }));
+        assert_eq!(info.get_build_wasm_path(), build_dir.join("my.app.wasm"));
+    }
+
+    #[test]
+    fn init_arg_is_inline_or_read_from_file() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("init.txt"), "(\"from file\")").unwrap();
+
+        let info = CanisterInfo::for_test("app", root.path(), serde_json::json!({}));
+        assert_eq!(info.get_init_arg().unwrap(), None);
+
+        let info = CanisterInfo::for_test(
+            "app",
+            root.path(),
+            serde_json::json!({ "init_arg": "(42)" }),
+        );
+        assert_eq!(info.get_init_arg().unwrap(), Some("(42)".to_string()));
+
+        let info = CanisterInfo::for_test(
+            "app",
+            root.path(),
+            serde_json::json!({ "init_arg_file": "init.txt" }),
+        );
+        assert_eq!(
+            info.get_init_arg().unwrap(),
+            Some("(\"from file\")".to_string())
+        );
+
+        let info = CanisterInfo::for_test(
+            "app",
+            root.path(),
+            serde_json::json!({ "init_arg": "(42)", "init_arg_file": "init.txt" }),
+        );
+        assert!(info.get_init_arg().is_err());
+
+        let info = CanisterInfo::for_test(
+            "app",
+            root.path(),
+            serde_json::json!({ "init_arg_file": "missing.txt" }),
+        );
+        assert!(info.get_init_arg().is_err());
+    }
+}
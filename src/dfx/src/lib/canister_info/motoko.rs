@@ -66,10 +66,10 @@ impl CanisterInfoFactory for MotokoCanisterInfo {
 
         let input_path = workspace_root.join(&main_path);
         let output_root = build_root.join(name);
-        let output_wasm_path = output_root.join(name).with_extension("wasm");
-        let output_idl_path = output_wasm_path.with_extension("did");
-        let output_did_js_path = output_wasm_path.with_extension("did.js");
-        let output_canister_js_path = output_wasm_path.with_extension("js");
+        let output_wasm_path = info.get_build_wasm_path();
+        let output_idl_path = output_root.join(name).with_extension("did");
+        let output_did_js_path = output_idl_path.with_extension("did.js");
+        let output_canister_js_path = output_idl_path.with_extension("js");
         let output_assets_root = output_root.join("assets");
 
         Ok(MotokoCanisterInfo {
@@ -1,18 +1,104 @@
-use crate::lib::error::{DfxError, DfxResult};
+use crate::config::cache;
+use crate::lib::error::{CacheError, DfxError, DfxResult};
 use crate::lib::manifest::Manifest;
 use crate::{error_invalid_argument, error_invalid_data};
 
-use indicatif::{ProgressBar, ProgressDrawTarget};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use libflate::gzip::Decoder;
+use reqwest::StatusCode;
 use semver::Version;
+use std::ffi::OsString;
 use std::fs;
-use std::io::Write;
-use std::path::Path;
+use std::io::Read;
+use std::time::Duration;
 use tar::Archive;
 
 pub static DEFAULT_RELEASE_ROOT: &str = "https://sdk.dfinity.org";
-pub static CACHE_ROOT: &str = ".cache/dfinity/versions/";
-pub static DOWNLOADS_DIR: &str = ".cache/dfinity/downloads/";
+
+const DOWNLOAD_ATTEMPTS: u32 = 5;
+const DOWNLOAD_RETRY_PAUSE: Duration = Duration::from_secs(1);
+const MAX_DOWNLOAD_RETRY_PAUSE: Duration = Duration::from_secs(60);
+
+/// Whether a download that failed with this status might succeed when retried: request
+/// timeouts, rate limiting and server errors. Other errors, like a 404 for a version that does
+/// not exist, fail the same way again.
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+}
+
+/// The pause requested by the `Retry-After` header of a response, if it is given in seconds.
+fn get_retry_after(response: &reqwest::blocking::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Download the content at `url`, retrying with exponential backoff on statuses for which
+/// `is_retryable_status` holds. The pause honors the `Retry-After` header of the response.
+/// `progress` is called with the number of bytes downloaded so far and the total size of the
+/// content, if the server sent it.
+fn download(url: &reqwest::Url, progress: &dyn Fn(u64, Option<u64>)) -> DfxResult<Vec<u8>> {
+    check_online(url, std::env::var_os("DFX_OFFLINE"))?;
+
+    let mut pause = DOWNLOAD_RETRY_PAUSE;
+    let mut attempt = 1;
+    loop {
+        let response = reqwest::blocking::get(url.clone()).map_err(DfxError::new)?;
+        let status = response.status();
+        if status.is_success() {
+            return read_with_progress(response, progress);
+        }
+        if !is_retryable_status(status) || attempt >= DOWNLOAD_ATTEMPTS {
+            return Err(DfxError::new(CacheError::DownloadFailed(
+                url.to_string(),
+                status,
+            )));
+        }
+        std::thread::sleep(
+            get_retry_after(&response)
+                .unwrap_or(pause)
+                .min(MAX_DOWNLOAD_RETRY_PAUSE),
+        );
+        pause *= 2;
+        attempt += 1;
+    }
+}
+
+/// Fail if downloads are disabled by the `DFX_OFFLINE` environment variable, given as
+/// `offline`, e.g. in CI or air-gapped environments where they would hang or fail late.
+fn check_online(url: &reqwest::Url, offline: Option<OsString>) -> DfxResult {
+    match offline {
+        Some(offline) if !offline.is_empty() && offline != "0" => {
+            Err(DfxError::new(CacheError::Offline(url.to_string())))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn read_with_progress(
+    mut response: reqwest::blocking::Response,
+    progress: &dyn Fn(u64, Option<u64>),
+) -> DfxResult<Vec<u8>> {
+    let total = response.content_length();
+    let mut content = Vec::with_capacity(total.unwrap_or(0) as usize);
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = response.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(content);
+        }
+        content.extend_from_slice(&buffer[..read]);
+        progress(content.len() as u64, total);
+    }
+}
 
 pub fn get_manifest() -> DfxResult<Manifest> {
     let url_string = format!("{}/manifest.json", DEFAULT_RELEASE_ROOT);
@@ -25,20 +111,10 @@ pub fn get_manifest() -> DfxResult<Manifest> {
     b.set_message(&format!("Fetching manifest {}", url));
     b.enable_steady_tick(80);
 
-    let response = reqwest::blocking::get(url).map_err(DfxError::new)?;
-    let status_code = response.status();
+    let content = download(&url, &|_, _| {});
     b.finish_and_clear();
 
-    if !status_code.is_success() {
-        return Err(error_invalid_data!(
-            "unable to fetch manifest: {}",
-            status_code.canonical_reason().unwrap_or("unknown error"),
-        ));
-    }
-
-    response
-        .json()
-        .map_err(|e| error_invalid_data!("invalid manifest: {}", e))
+    serde_json::from_slice(&content?).map_err(|e| error_invalid_data!("invalid manifest: {}", e))
 }
 
 // Download a SDK version to cache
@@ -54,10 +130,7 @@ pub fn install_version(version: &Version) -> DfxResult<()> {
     ))
     .map_err(|e| error_invalid_argument!("invalid url: {}", e))?;
 
-    let home = std::env::var("HOME")?;
-    let home = Path::new(&home);
-
-    let download_dir = home.join(DOWNLOADS_DIR);
+    let download_dir = cache::get_cache_root()?.join("downloads");
     if !download_dir.exists() {
         fs::create_dir_all(&download_dir)?;
     }
@@ -65,19 +138,25 @@ pub fn install_version(version: &Version) -> DfxResult<()> {
     if download_file.exists() {
         println!("Found downloaded file {:?}", download_file);
     } else {
-        let mut dest = fs::File::create(&download_file)?;
-        let b = ProgressBar::new_spinner();
+        let b = ProgressBar::new(0);
         b.set_draw_target(ProgressDrawTarget::stderr());
+        b.set_style(
+            ProgressStyle::default_bar()
+                .template("{msg}\n{bytes}/{total_bytes} [{bar:40}] {bytes_per_sec}"),
+        );
         b.set_message(&format!("Downloading {}", url));
-        b.enable_steady_tick(80);
-        let response = reqwest::blocking::get(url).map_err(DfxError::new)?;
-        let content = response.bytes()?;
-        dest.write_all(&*content)?;
+        // Only create the file once the download succeeded, as an existing file is reused.
+        let content = download(&url, &|downloaded, total| {
+            if let Some(total) = total {
+                b.set_length(total);
+            }
+            b.set_position(downloaded);
+        })?;
+        fs::write(&download_file, content)?;
         b.finish_with_message("Download complete");
     }
 
-    let mut cache_dir = home.join(CACHE_ROOT);
-    cache_dir.push(version.to_string());
+    let cache_dir = cache::get_bin_cache(&version.to_string())?;
     if !cache_dir.exists() {
         fs::create_dir_all(&cache_dir)?;
     }
@@ -101,3 +180,83 @@ pub fn install_version(version: &Version) -> DfxResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_timeouts_rate_limits_and_server_errors_are_retried() {
+        assert!(is_retryable_status(StatusCode::REQUEST_TIMEOUT));
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::FORBIDDEN));
+    }
+
+    #[test]
+    fn download_retries_server_errors_and_reports_the_final_status() {
+        let _m = mockito::mock("GET", "/dist/unavailable.tar.gz")
+            .with_status(503)
+            .with_header("retry-after", "0")
+            .expect(DOWNLOAD_ATTEMPTS as usize)
+            .create();
+        let url = reqwest::Url::parse(&mockito::server_url())
+            .unwrap()
+            .join("/dist/unavailable.tar.gz")
+            .unwrap();
+        let err = download(&url, &|_, _| {}).unwrap_err();
+        assert!(err.to_string().contains("503"));
+        _m.assert();
+
+        let _m = mockito::mock("GET", "/dist/missing.tar.gz")
+            .with_status(404)
+            .expect(1)
+            .create();
+        let url = url.join("/dist/missing.tar.gz").unwrap();
+        let err = download(&url, &|_, _| {}).unwrap_err();
+        assert!(err.to_string().contains("404"));
+        _m.assert();
+    }
+
+    #[test]
+    fn offline_mode_prevents_downloads() {
+        let url = reqwest::Url::parse("https://sdk.dfinity.org/manifest.json").unwrap();
+        assert!(check_online(&url, None).is_ok());
+        assert!(check_online(&url, Some(OsString::from(""))).is_ok());
+        assert!(check_online(&url, Some(OsString::from("0"))).is_ok());
+
+        let err = check_online(&url, Some(OsString::from("1"))).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CacheError>(),
+            Some(CacheError::Offline(_))
+        ));
+    }
+
+    #[test]
+    fn download_reports_increasing_progress() {
+        let body = vec![7u8; 200 * 1024];
+        let _m = mockito::mock("GET", "/dist/large.tar.gz")
+            .with_body(&body)
+            .create();
+        let url = reqwest::Url::parse(&mockito::server_url())
+            .unwrap()
+            .join("/dist/large.tar.gz")
+            .unwrap();
+
+        let calls = std::cell::RefCell::new(vec![]);
+        let content = download(&url, &|downloaded, total| {
+            calls.borrow_mut().push((downloaded, total))
+        })
+        .unwrap();
+        assert_eq!(content, body);
+
+        let calls = calls.into_inner();
+        assert!(!calls.is_empty());
+        assert!(calls.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(
+            calls.last(),
+            Some(&(body.len() as u64, Some(body.len() as u64)))
+        );
+    }
+}
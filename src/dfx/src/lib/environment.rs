@@ -1,7 +1,7 @@
 use crate::config::cache::{Cache, DiskBasedCache};
 use crate::config::dfinity::Config;
 use crate::config::{cache, dfx_version};
-use crate::lib::error::DfxResult;
+use crate::lib::error::{DfxError, DfxResult, ProjectError};
 use crate::lib::identity::identity_manager::IdentityManager;
 use crate::lib::network::network_descriptor::NetworkDescriptor;
 use crate::lib::progress_bar::ProgressBar;
@@ -9,7 +9,7 @@ use crate::lib::progress_bar::ProgressBar;
 use anyhow::{anyhow, Context};
 use ic_agent::{Agent, Identity};
 use ic_types::Principal;
-use semver::Version;
+use semver::{Version, VersionReq};
 use slog::{Logger, Record};
 use std::collections::BTreeMap;
 use std::fs::create_dir_all;
@@ -74,16 +74,7 @@ pub struct EnvironmentImpl {
 
 impl EnvironmentImpl {
     pub fn new() -> DfxResult<Self> {
-        let config = match Config::from_current_dir() {
-            Err(err) => {
-                if err.kind() == std::io::ErrorKind::NotFound {
-                    Ok(None)
-                } else {
-                    Err(err)
-                }
-            }
-            Ok(x) => Ok(Some(x)),
-        }?;
+        let config = Config::from_current_dir()?;
         let temp_dir = match &config {
             None => tempfile::tempdir()
                 .expect("Could not create a temporary directory.")
@@ -95,7 +86,8 @@ impl EnvironmentImpl {
         // Figure out which version of DFX we should be running. This will use the following
         // fallback sequence:
         //   1. DFX_VERSION environment variable
-        //   2. dfx.json "dfx" field
+        //   2. dfx.json "dfx" field, which can also be a semver range like "^0.8" resolving to
+        //      the highest matching version installed in the cache, this one included
         //   3. this binary's version
         // If any of those are empty string, we stop the fallback and use the current version.
         // If any of those are a valid version, we try to use that directly as is.
@@ -105,7 +97,11 @@ impl EnvironmentImpl {
                 None => dfx_version().clone(),
                 Some(c) => match &c.get_config().get_dfx() {
                     None => dfx_version().clone(),
-                    Some(v) => Version::parse(&v)?,
+                    Some(v) => resolve_dfx_version(
+                        &v,
+                        &cache::list_versions().unwrap_or_default(),
+                        dfx_version(),
+                    )?,
                 },
             },
             Ok(v) => {
@@ -144,6 +140,30 @@ impl EnvironmentImpl {
     }
 }
 
+/// Resolves the "dfx" field of dfx.json. An exact version is used as is, while a range resolves
+/// to the highest of the installed and running versions that matches it.
+fn resolve_dfx_version(spec: &str, installed: &[Version], running: &Version) -> DfxResult<Version> {
+    if let Ok(version) = Version::parse(spec) {
+        return Ok(version);
+    }
+    let req = VersionReq::parse(spec)
+        .with_context(|| format!("Invalid dfx version or version range '{}'.", spec))?;
+    installed
+        .iter()
+        .chain(std::iter::once(running))
+        .filter(|version| req.matches(version))
+        .max()
+        .cloned()
+        .ok_or_else(|| {
+            anyhow!(
+                "No installed version of dfx matches '{}' (running {}). Install a matching version \
+                 with `dfx toolchain install`, or change the \"dfx\" field of dfx.json.",
+                spec,
+                running
+            )
+        })
+}
+
 impl Environment for EnvironmentImpl {
     fn get_cache(&self) -> Arc<dyn Cache> {
         Arc::clone(&self.cache)
@@ -154,9 +174,8 @@ impl Environment for EnvironmentImpl {
     }
 
     fn get_config_or_anyhow(&self) -> anyhow::Result<Arc<Config>> {
-        self.get_config().ok_or_else(|| anyhow!(
-            "Cannot find dfx configuration file in the current working directory. Did you forget to create one?"
-        ))
+        self.get_config()
+            .ok_or_else(|| DfxError::new(ProjectError::CommandMustBeRunInAProject()))
     }
 
     fn is_in_project(&self) -> bool {
@@ -223,6 +242,7 @@ pub struct AgentEnvironment<'a> {
     agent: Agent,
     network_descriptor: NetworkDescriptor,
     identity_manager: IdentityManager,
+    cache_override: Option<Arc<dyn Cache>>,
 }
 
 impl<'a> AgentEnvironment<'a> {
@@ -241,13 +261,26 @@ impl<'a> AgentEnvironment<'a> {
                 .expect("Failed to construct agent."),
             network_descriptor,
             identity_manager,
+            cache_override: None,
         })
     }
+
+    /// Use a different cache than the one of the backend, e.g. to build with the binaries of
+    /// another SDK version.
+    pub fn with_cache(self, cache: Arc<dyn Cache>) -> Self {
+        Self {
+            cache_override: Some(cache),
+            ..self
+        }
+    }
 }
 
 impl<'a> Environment for AgentEnvironment<'a> {
     fn get_cache(&self) -> Arc<dyn Cache> {
-        self.backend.get_cache()
+        match &self.cache_override {
+            Some(cache) => Arc::clone(cache),
+            None => self.backend.get_cache(),
+        }
     }
 
     fn get_config(&self) -> Option<Arc<Config>> {
@@ -255,9 +288,8 @@ impl<'a> Environment for AgentEnvironment<'a> {
     }
 
     fn get_config_or_anyhow(&self) -> anyhow::Result<Arc<Config>> {
-        self.get_config().ok_or_else(|| anyhow!(
-            "Cannot find dfx configuration file in the current working directory. Did you forget to create one?"
-        ))
+        self.get_config()
+            .ok_or_else(|| DfxError::new(ProjectError::CommandMustBeRunInAProject()))
     }
 
     fn is_in_project(&self) -> bool {
@@ -464,3 +496,50 @@ fn create_agent(
                 .ok()
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(versions: &[&str]) -> Vec<Version> {
+        versions
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn exact_dfx_version_is_used_as_is() {
+        let running = Version::parse("0.8.0").unwrap();
+        let version = resolve_dfx_version("0.7.2", &[], &running).unwrap();
+        assert_eq!(version, Version::parse("0.7.2").unwrap());
+    }
+
+    #[test]
+    fn dfx_version_range_resolves_to_highest_installed_match() {
+        let running = Version::parse("0.8.0").unwrap();
+        let installed = versions(&["0.7.0", "0.7.2", "0.6.26"]);
+        let version = resolve_dfx_version("^0.7", &installed, &running).unwrap();
+        assert_eq!(version, Version::parse("0.7.2").unwrap());
+    }
+
+    #[test]
+    fn dfx_version_range_can_resolve_to_running_version() {
+        let running = Version::parse("0.8.1").unwrap();
+        let installed = versions(&["0.7.2", "0.8.0"]);
+        let version = resolve_dfx_version(">=0.8.1", &installed, &running).unwrap();
+        assert_eq!(version, running);
+    }
+
+    #[test]
+    fn dfx_version_range_without_match_is_an_error() {
+        let running = Version::parse("0.8.0").unwrap();
+        let installed = versions(&["0.7.2"]);
+        let err = resolve_dfx_version("^0.5", &installed, &running).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No installed version of dfx matches '^0.5'"));
+
+        assert!(resolve_dfx_version("not a version", &installed, &running).is_err());
+    }
+}
@@ -24,6 +24,12 @@ pub enum BuildError {
     #[error("The command '{0}' failed with exit status '{1}'.\nStdout:\n{2}\nStderr:\n{3}")]
     CommandError(String, ExitStatus, String, String),
 
+    #[error("The compiler crashed with signal {1} while running '{0}'. This is a bug in the compiler, please report it at https://github.com/dfinity/motoko/issues.\nStderr:\n{2}")]
+    CompilerCrashed(String, i32, String),
+
+    #[error("The args of canister '{0}' cannot contain '{1}', which is set by dfx.")]
+    ReservedMocArgument(String, String),
+
     #[error("The dependency analyzer failed: {0}")]
     DependencyError(String),
 
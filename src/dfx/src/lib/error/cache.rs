@@ -14,4 +14,28 @@ pub enum CacheError {
 
     #[error("Unknown version '{0}'.")]
     UnknownVersion(String),
+
+    #[error("Version '{0}' is not installed.")]
+    VersionNotInstalled(String),
+
+    #[error("Version '{0}' is the version of dfx being run. Use --force to delete it anyway.")]
+    CannotDeleteRunningVersion(String),
+
+    #[error("The binary '{0}' bundled with this dfx does not match the checksum it was built with. Reinstall dfx.")]
+    CorruptedBinaryCache(String),
+
+    #[error("The cache of version '{0}' is corrupted, these files do not match their checksums: {1}. Reinstall it with 'dfx cache install'.")]
+    ChecksumMismatch(String, String),
+
+    #[error("Unknown binary '{0}'.")]
+    UnknownBinary(String),
+
+    #[error("Cannot download '{0}' in offline mode. Unset DFX_OFFLINE or remove --offline to allow downloads.")]
+    Offline(String),
+
+    #[error("Failed to download '{0}': {1}.")]
+    DownloadFailed(String, reqwest::StatusCode),
+
+    #[error("Cannot find binary '{name}' in the cache for version {version}. Try reinstalling it with 'dfx cache install'.")]
+    BinaryNotFound { name: String, version: String },
 }
@@ -1,10 +1,12 @@
 pub mod build;
 pub mod cache;
 pub mod identity;
+pub mod project;
 
 pub use build::BuildError;
 pub use cache::CacheError;
 pub use identity::IdentityError;
+pub use project::ProjectError;
 
 /// The type to represent DFX results.
 pub type DfxResult<T = ()> = anyhow::Result<T>;
@@ -12,10 +14,50 @@ pub type DfxResult<T = ()> = anyhow::Result<T>;
 /// The type to represent DFX errors.
 pub type DfxError = anyhow::Error;
 
+/// An argument passed to dfx was invalid.
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid argument: {0}")]
+pub struct InvalidArgumentError(pub String);
+
+/// A command that dfx ran in place of itself, like a plugin, exited with a failure. dfx exits
+/// with the same code.
+#[derive(thiserror::Error, Debug)]
+#[error("The command '{0}' exited with code {1}.")]
+pub struct ChildExitError(pub String, pub i32);
+
+/// Exit code for invalid usage, e.g. unknown flags or invalid arguments.
+pub const EXIT_CODE_USAGE: i32 = 2;
+/// Exit code for canisters that failed to build.
+pub const EXIT_CODE_BUILD: i32 = 3;
+/// Exit code for errors talking to the replica.
+pub const EXIT_CODE_NETWORK: i32 = 4;
+/// Exit code for all other errors.
+pub const EXIT_CODE_INTERNAL: i32 = 70;
+
+/// Select the exit code of dfx for an error, based on the first error in its chain that
+/// belongs to a known category. A failed child command passes its own exit code through.
+pub fn exit_code(err: &DfxError) -> i32 {
+    for cause in err.chain() {
+        if let Some(ChildExitError(_, code)) = cause.downcast_ref::<ChildExitError>() {
+            return *code;
+        }
+        if cause.is::<InvalidArgumentError>() || cause.is::<clap::Error>() {
+            return EXIT_CODE_USAGE;
+        }
+        if cause.is::<BuildError>() {
+            return EXIT_CODE_BUILD;
+        }
+        if cause.is::<ic_agent::AgentError>() || cause.is::<reqwest::Error>() {
+            return EXIT_CODE_NETWORK;
+        }
+    }
+    EXIT_CODE_INTERNAL
+}
+
 #[macro_export]
 macro_rules! error_invalid_argument {
     ($($args:tt)*) => {
-        anyhow::anyhow!("Invalid argument: {}", format_args!($($args)*))
+        anyhow::Error::new($crate::lib::error::InvalidArgumentError(format!($($args)*)))
     }
 }
 
@@ -39,3 +81,41 @@ macro_rules! error_unknown {
         anyhow::anyhow!("Unknown error: {}", format_args!($($args)*))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_code_depends_on_error_category() {
+        let usage = anyhow::Error::new(InvalidArgumentError("bad".to_string()));
+        assert_eq!(exit_code(&usage), EXIT_CODE_USAGE);
+
+        let clap_error = clap::App::new("dfx")
+            .try_get_matches_from(vec!["dfx", "--unknown-flag"])
+            .unwrap_err();
+        assert_eq!(exit_code(&clap_error.into()), EXIT_CODE_USAGE);
+
+        let build = anyhow::Error::new(BuildError::DependencyError("cycle".to_string()));
+        assert_eq!(exit_code(&build), EXIT_CODE_BUILD);
+
+        let network = anyhow::Error::new(ic_agent::AgentError::MessageError("down".to_string()));
+        assert_eq!(exit_code(&network), EXIT_CODE_NETWORK);
+
+        let internal = anyhow::anyhow!("Something went wrong.");
+        assert_eq!(exit_code(&internal), EXIT_CODE_INTERNAL);
+    }
+
+    #[test]
+    fn exit_code_looks_through_context() {
+        let build = anyhow::Error::new(BuildError::DependencyError("cycle".to_string()))
+            .context("Failed to build canister 'app'.");
+        assert_eq!(exit_code(&build), EXIT_CODE_BUILD);
+    }
+
+    #[test]
+    fn exit_code_of_a_failed_child_is_passed_through() {
+        let child = anyhow::Error::new(ChildExitError("dfx-hello".to_string(), 5));
+        assert_eq!(exit_code(&child), 5);
+    }
+}
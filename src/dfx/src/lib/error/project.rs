@@ -0,0 +1,7 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ProjectError {
+    #[error("Cannot find dfx configuration file in the current working directory. Run 'dfx new' to create a project, or 'cd' into an existing one.")]
+    CommandMustBeRunInAProject(),
+}
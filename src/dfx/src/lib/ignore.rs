@@ -0,0 +1,220 @@
+use crate::lib::error::DfxResult;
+
+use anyhow::Context;
+use std::path::{Component, Path};
+
+/// The name of the ignore file, looked up at the root of a project.
+pub const IGNORE_FILE_NAME: &str = ".dfxignore";
+
+/// A single line of an ignore file.
+struct IgnorePattern {
+    /// The pattern, split on `/`.
+    segments: Vec<String>,
+    /// Whether the pattern is relative to the project root. Patterns that contain a slash
+    /// (other than a trailing one) are anchored; others match a file name at any depth.
+    anchored: bool,
+    /// Whether the pattern ends with a slash and only matches directories.
+    dir_only: bool,
+    /// Whether the pattern starts with `!` and re-includes paths excluded by earlier patterns.
+    negated: bool,
+}
+
+impl IgnorePattern {
+    fn parse(line: &str) -> Option<IgnorePattern> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        if line.is_empty() {
+            return None;
+        }
+
+        Some(IgnorePattern {
+            segments: line.split('/').map(String::from).collect(),
+            anchored,
+            dir_only,
+            negated,
+        })
+    }
+
+    fn matches(&self, components: &[String], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            match_segments(&self.segments, components)
+        } else {
+            components.last().map_or(false, |name| {
+                match_segments(&self.segments, &[name.clone()])
+            })
+        }
+    }
+}
+
+/// Matches paths against the rules of a `.dfxignore` file.
+///
+/// The file uses gitignore syntax: blank lines and lines starting with `#` are skipped, `*` and
+/// `?` match within a path segment, `[...]` matches a character class, `**` matches any number
+/// of directories, a trailing `/` only matches directories, a leading `/` or any inner `/`
+/// anchors the pattern to the project root, and a leading `!` re-includes a path. As in git,
+/// a path cannot be re-included if one of its parent directories is ignored.
+#[derive(Default)]
+pub struct IgnoreMatcher {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreMatcher {
+    /// Load the `.dfxignore` file at the root of a project. A missing file ignores nothing.
+    pub fn from_project_root(project_root: &Path) -> DfxResult<IgnoreMatcher> {
+        let path = project_root.join(IGNORE_FILE_NAME);
+        if !path.exists() {
+            return Ok(IgnoreMatcher::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read '{}'.", path.display()))?;
+        Ok(IgnoreMatcher::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> IgnoreMatcher {
+        IgnoreMatcher {
+            patterns: content.lines().filter_map(IgnorePattern::parse).collect(),
+        }
+    }
+
+    /// Whether a path, relative to the project root, is ignored. `is_dir` tells whether the
+    /// path itself is a directory; all of its parents are.
+    pub fn is_ignored(&self, relative_path: &Path, is_dir: bool) -> bool {
+        if self.patterns.is_empty() {
+            return false;
+        }
+
+        let components: Vec<String> = relative_path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+
+        (1..=components.len()).any(|len| {
+            let is_dir = is_dir || len < components.len();
+            self.is_ignored_exactly(&components[..len], is_dir)
+        })
+    }
+
+    fn is_ignored_exactly(&self, components: &[String], is_dir: bool) -> bool {
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(components, is_dir))
+            .map_or(false, |pattern| !pattern.negated)
+    }
+}
+
+/// Match pattern segments against path components, where a `**` segment matches any number
+/// of components.
+fn match_segments(segments: &[String], components: &[String]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((segment, rest)) if segment == "**" => {
+            (0..=components.len()).any(|skip| match_segments(rest, &components[skip..]))
+        }
+        Some((segment, rest)) => match components.split_first() {
+            Some((component, components)) => {
+                let pattern: Vec<char> = segment.chars().collect();
+                let name: Vec<char> = component.chars().collect();
+                match_wildcard(&pattern, &name) && match_segments(rest, components)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match a single path segment against a pattern supporting `*`, `?` and `[...]`.
+fn match_wildcard(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| match_wildcard(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && match_wildcard(rest, &name[1..]),
+        Some(('[', rest)) => match (rest.iter().position(|&c| c == ']'), name.split_first()) {
+            (Some(end), Some((&c, name))) if end > 0 => {
+                let (negated, class) = match rest[0] {
+                    '!' | '^' => (true, &rest[1..end]),
+                    _ => (false, &rest[..end]),
+                };
+                match_class(class, c) != negated && match_wildcard(&rest[end + 1..], name)
+            }
+            // An unterminated class is matched literally.
+            (None, Some(('[', name))) => match_wildcard(rest, name),
+            _ => false,
+        },
+        Some((&p, rest)) => match name.split_first() {
+            Some((&c, name)) => p == c && match_wildcard(rest, name),
+            None => false,
+        },
+    }
+}
+
+fn match_class(class: &[char], c: char) -> bool {
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            if class[i] <= c && c <= class[i + 2] {
+                return true;
+            }
+            i += 3;
+        } else {
+            if class[i] == c {
+                return true;
+            }
+            i += 1;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unanchored_patterns_match_at_any_depth() {
+        let matcher = IgnoreMatcher::parse("# comment\n\n*.map\nnode_modules/\n");
+        assert!(matcher.is_ignored(Path::new("main.js.map"), false));
+        assert!(matcher.is_ignored(Path::new("assets/js/main.js.map"), false));
+        assert!(!matcher.is_ignored(Path::new("assets/js/main.js"), false));
+        assert!(matcher.is_ignored(Path::new("src/node_modules"), true));
+        assert!(matcher.is_ignored(Path::new("src/node_modules/a/index.js"), false));
+        assert!(!matcher.is_ignored(Path::new("src/node_modules"), false));
+    }
+
+    #[test]
+    fn anchored_patterns_match_from_the_root() {
+        let matcher = IgnoreMatcher::parse("/dist\nsrc/**/test[0-9].txt\n");
+        assert!(matcher.is_ignored(Path::new("dist"), true));
+        assert!(!matcher.is_ignored(Path::new("src/dist"), true));
+        assert!(matcher.is_ignored(Path::new("src/test1.txt"), false));
+        assert!(matcher.is_ignored(Path::new("src/a/b/test2.txt"), false));
+        assert!(!matcher.is_ignored(Path::new("src/a/b/testx.txt"), false));
+    }
+
+    #[test]
+    fn negated_patterns_reinclude() {
+        let matcher = IgnoreMatcher::parse("*.txt\n!keep.txt\nbuild/\n!build/keep.txt\n");
+        assert!(matcher.is_ignored(Path::new("a.txt"), false));
+        assert!(!matcher.is_ignored(Path::new("keep.txt"), false));
+        // A file cannot be re-included if its parent directory is ignored.
+        assert!(matcher.is_ignored(Path::new("build/keep.txt"), false));
+    }
+}
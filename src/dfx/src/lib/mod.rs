@@ -1,4 +1,5 @@
 pub mod builders;
+pub mod candid;
 pub mod canister_info;
 pub mod config;
 pub mod dist;
@@ -6,6 +7,7 @@ pub mod environment;
 pub mod error;
 pub mod ic_attributes;
 pub mod identity;
+pub mod ignore;
 pub mod installers;
 pub mod locations;
 pub mod logger;
@@ -23,4 +25,5 @@ pub mod root_key;
 pub mod sign;
 pub mod toolchain;
 pub mod waiter;
+pub mod wasm;
 pub mod webserver;
@@ -1,24 +1,27 @@
 use crate::config::dfinity::Config;
 use crate::lib::builders::{
-    BuildConfig, BuildOutput, BuilderPool, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
+    format_js_files, BuildConfig, BuildOutput, BuilderPool, CanisterBuilder, IdlBuildOutput,
+    WasmBuildOutput,
 };
 use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
 use crate::lib::error::{BuildError, DfxError, DfxResult};
 use crate::lib::models::canister_id_store::CanisterIdStore;
+use crate::lib::wasm::set_custom_section;
 use crate::util::{assets, check_candid_file};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
 use ic_types::principal::Principal as CanisterId;
 use petgraph::graph::{DiGraph, NodeIndex};
 use rand::{thread_rng, RngCore};
-use slog::Logger;
+use slog::{debug, Logger};
 use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::io::Read;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Represents a canister from a DFX project. It can be a virtual Canister.
 /// Multiple canister instances can have the same info, but would be differentiated
@@ -29,6 +32,15 @@ pub struct Canister {
     info: CanisterInfo,
     builder: Arc<dyn CanisterBuilder>,
     output: RefCell<Option<BuildOutput>>,
+    timings: RefCell<Option<BuildTimings>>,
+}
+
+/// The time spent in each step of the last successful build of a canister.
+#[derive(Clone, Copy, Debug)]
+pub struct BuildTimings {
+    pub prebuild: Duration,
+    pub build: Duration,
+    pub postbuild: Duration,
 }
 
 impl Canister {
@@ -39,6 +51,7 @@ impl Canister {
             info,
             builder,
             output: RefCell::new(None),
+            timings: RefCell::new(None),
         }
     }
 
@@ -66,6 +79,10 @@ impl Canister {
         self.info.get_name()
     }
 
+    pub fn get_info(&self) -> &CanisterInfo {
+        &self.info
+    }
+
     pub fn canister_id(&self) -> CanisterId {
         self.info.get_canister_id().unwrap()
     }
@@ -84,6 +101,11 @@ impl Canister {
         unsafe { (&*self.output.as_ptr()).as_ref() }
     }
 
+    /// Get the time spent in each step of the last successful build, if any.
+    pub fn get_build_timings(&self) -> Option<BuildTimings> {
+        *self.timings.borrow()
+    }
+
     pub fn generate(&self, pool: &CanisterPool, build_config: &BuildConfig) -> DfxResult {
         self.builder.generate(pool, &self.info, build_config)
     }
@@ -129,16 +151,25 @@ impl CanisterPool {
         env: &dyn Environment,
         generate_cid: bool,
         canister_names: &[String],
+    ) -> DfxResult<Self> {
+        let config = env.get_config_or_anyhow()?;
+
+        CanisterPool::load_with_config(env, &config, generate_cid, canister_names)
+    }
+
+    /// Same as `load`, but with a configuration other than the one of the environment.
+    pub fn load_with_config(
+        env: &dyn Environment,
+        config: &Config,
+        generate_cid: bool,
+        canister_names: &[String],
     ) -> DfxResult<Self> {
         let logger = env.get_logger().new(slog::o!());
-        let config = env
-            .get_config()
-            .ok_or_else(|| anyhow!("Cannot find dfx configuration file in the current working directory. Did you forget to create one?"))?;
 
         let mut canisters_map = Vec::new();
 
         let mut pool_helper = PoolConstructHelper {
-            config: &config,
+            config,
             builder_pool: BuilderPool::new(env)?,
             canister_id_store: CanisterIdStore::for_env(env)?,
             generate_cid,
@@ -277,6 +308,10 @@ impl CanisterPool {
             std::fs::set_permissions(&wasm_file_path, perms)?;
         }
 
+        if build_config.embed_git {
+            embed_git_sources(&wasm_file_path, canister.info.get_workspace_root())?;
+        }
+
         // And then create an canisters/IDL folder with canister DID files per canister ID.
         let idl_root = &build_config.idl_root;
         let canister_id = canister.canister_id();
@@ -292,6 +327,48 @@ impl CanisterPool {
         canister.postbuild(self, build_config)
     }
 
+    /// Run the prebuild, build and postbuild steps of a single canister, recording the time
+    /// spent in each step on the canister and logging it at the debug level.
+    fn step_build_canister<'a>(
+        &self,
+        build_config: &BuildConfig,
+        canister: &'a Canister,
+    ) -> Result<&'a BuildOutput, BuildError> {
+        let canister_id = canister.canister_id();
+
+        let start = Instant::now();
+        self.step_prebuild(build_config, canister)
+            .map_err(|e| BuildError::PreBuildStepFailed(canister_id, Box::new(e)))?;
+        let prebuild_time = start.elapsed();
+
+        let start = Instant::now();
+        let output = self
+            .step_build(build_config, canister)
+            .map_err(|e| BuildError::BuildStepFailed(canister_id, Box::new(e)))?;
+        let build_time = start.elapsed();
+
+        let start = Instant::now();
+        self.step_postbuild(build_config, canister, output)
+            .map_err(|e| BuildError::PostBuildStepFailed(canister_id, Box::new(e)))?;
+        let timings = BuildTimings {
+            prebuild: prebuild_time,
+            build: build_time,
+            postbuild: start.elapsed(),
+        };
+
+        debug!(
+            self.logger,
+            "Built canister '{}': prebuild {:.2?}, build {:.2?}, postbuild {:.2?}.",
+            canister.get_name(),
+            timings.prebuild,
+            timings.build,
+            timings.postbuild
+        );
+        canister.timings.replace(Some(timings));
+
+        Ok(output)
+    }
+
     fn step_postbuild_all(
         &self,
         build_config: &BuildConfig,
@@ -339,21 +416,7 @@ impl CanisterPool {
         let mut result = Vec::new();
         for canister_id in &order {
             if let Some(canister) = self.get_canister(canister_id) {
-                result.push(
-                    self.step_prebuild(&build_config, canister)
-                        .map_err(|e| BuildError::PreBuildStepFailed(*canister_id, Box::new(e)))
-                        .and_then(|_| {
-                            self.step_build(&build_config, canister)
-                                .map_err(|e| BuildError::BuildStepFailed(*canister_id, Box::new(e)))
-                        })
-                        .and_then(|o| {
-                            self.step_postbuild(&build_config, canister, o)
-                                .map_err(|e| {
-                                    BuildError::PostBuildStepFailed(*canister_id, Box::new(e))
-                                })
-                                .map(|_| o)
-                        }),
-                );
+                result.push(self.step_build_canister(&build_config, canister));
             }
         }
 
@@ -376,6 +439,34 @@ impl CanisterPool {
     }
 }
 
+/// Record the current git commit of the project, and whether the working tree has uncommitted
+/// changes, in the `dfx:sources` custom section of a wasm module. Does nothing if the project
+/// is not in a git repository.
+fn embed_git_sources(wasm_path: &Path, project_root: &Path) -> DfxResult {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(project_root)
+            .output()
+    };
+    let commit = match git(&["rev-parse", "HEAD"]) {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        _ => return Ok(()),
+    };
+    let status = git(&["status", "--porcelain"]).context("Failed to run git status.")?;
+    let dirty = !status.stdout.is_empty();
+
+    let payload = serde_json::json!({ "commit": commit, "dirty": dirty }).to_string();
+    let wasm = std::fs::read(wasm_path)
+        .with_context(|| format!("Failed to read {}.", wasm_path.display()))?;
+    let wasm = set_custom_section(&wasm, "dfx:sources", payload.as_bytes())?;
+    std::fs::write(wasm_path, wasm)
+        .with_context(|| format!("Failed to write {}.", wasm_path.display()))?;
+    Ok(())
+}
+
 fn decode_path_to_str(path: &Path) -> DfxResult<&str> {
     path.to_str().ok_or_else(|| {
         DfxError::new(BuildError::JsBindGenError(format!(
@@ -394,9 +485,9 @@ fn build_canister_js(canister_id: &CanisterId, canister_info: &CanisterInfo) ->
 
     let (env, ty) = check_candid_file(&canister_info.get_build_idl_path())?;
     let content = ensure_trailing_newline(candid::bindings::javascript::compile(&env, &ty));
-    std::fs::write(output_did_js_path, content)?;
+    std::fs::write(&output_did_js_path, content)?;
     let content = ensure_trailing_newline(candid::bindings::typescript::compile(&env, &ty));
-    std::fs::write(output_did_ts_path, content)?;
+    std::fs::write(&output_did_ts_path, content)?;
 
     let mut language_bindings = assets::language_bindings()?;
     let index_js_path = canister_info.get_index_js_path();
@@ -424,6 +515,11 @@ fn build_canister_js(canister_id: &CanisterId, canister_info: &CanisterInfo) ->
         }
     }
 
+    format_js_files(
+        canister_info,
+        &[output_did_js_path, output_did_ts_path, index_js_path],
+    )?;
+
     Ok(())
 }
 
@@ -0,0 +1,38 @@
+use crate::lib::nns_types::Memo;
+
+/// The memos the cycles minting canister recognizes in the transfers it is notified of.
+/// Transfers with any other memo are refunded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KnownMemo {
+    /// Create a canister controlled by the principal of the destination subaccount.
+    CreateCanister,
+    /// Top up the canister of the destination subaccount.
+    TopUp,
+}
+
+impl KnownMemo {
+    pub fn to_memo(self) -> Memo {
+        match self {
+            // "CREA" in ASCII, as little-endian bytes
+            KnownMemo::CreateCanister => Memo(1095062083),
+            // "TPUP" in ASCII, as little-endian bytes
+            KnownMemo::TopUp => Memo(1347768404),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_memos_are_the_ones_of_the_cycles_minting_canister() {
+        assert_eq!(KnownMemo::CreateCanister.to_memo(), Memo(1095062083));
+        assert_eq!(KnownMemo::TopUp.to_memo(), Memo(1347768404));
+        assert_eq!(
+            KnownMemo::CreateCanister.to_memo().0.to_le_bytes()[..4],
+            *b"CREA"
+        );
+        assert_eq!(KnownMemo::TopUp.to_memo().0.to_le_bytes()[..4], *b"TPUP");
+    }
+}
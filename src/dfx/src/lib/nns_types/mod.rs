@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod account_identifier;
 pub mod icpts;
+pub mod known_memo;
 
 pub const CYCLE_MINTER_CANISTER_ID: &str = "rkp4c-7iaaa-aaaaa-aaaca-cai";
 pub const LEDGER_CANISTER_ID: &str = "ryjl3-tyaaa-aaaaa-aaaba-cai";
@@ -32,9 +32,7 @@ pub async fn deploy_canisters(
 ) -> DfxResult {
     let log = env.get_logger();
 
-    let config = env
-        .get_config()
-        .ok_or_else(|| anyhow!("Cannot find dfx configuration file in the current working directory. Did you forget to create one?"))?;
+    let config = env.get_config_or_anyhow()?;
     let initial_canister_id_store = CanisterIdStore::for_env(env)?;
 
     let canister_names = canisters_to_deploy(&config, some_canister)?;
@@ -1,4 +1,6 @@
-use crate::config::dfinity::{Config, ConfigNetwork, NetworkType, DEFAULT_IC_GATEWAY};
+use crate::config::dfinity::{
+    Config, ConfigInterface, ConfigNetwork, NetworkType, DEFAULT_IC_GATEWAY,
+};
 use crate::lib::environment::{AgentEnvironment, Environment};
 use crate::lib::error::DfxResult;
 use crate::lib::network::network_descriptor::NetworkDescriptor;
@@ -33,13 +35,52 @@ pub fn get_network_descriptor<'a>(
     env: &'a (dyn Environment + 'a),
     network: Option<String>,
 ) -> DfxResult<NetworkDescriptor> {
-    set_network_context(network);
+    let network_is_explicit = network.is_some();
     let config = env.get_config().unwrap_or_else(|| {
         eprintln!("dfx.json not found, using default.");
         Arc::new(Config::from_str("{}").unwrap())
     });
     let config = config.as_ref().get_config();
+    set_network_context(network.or_else(|| config.default_network.clone()));
     let network_name = get_network_context()?;
+
+    resolve_network_descriptor(
+        config,
+        network_name,
+        network_is_explicit,
+        get_replica_url_override,
+    )
+}
+
+/// Resolve the descriptor of a network of the configuration. Unless the network was given
+/// explicitly, the replica URL of a local or ephemeral network can be overridden from the
+/// environment (e.g. in CI, where the replica is not running on localhost).
+fn resolve_network_descriptor(
+    config: &ConfigInterface,
+    network_name: String,
+    network_is_explicit: bool,
+    replica_url_override: impl FnOnce() -> DfxResult<Option<String>>,
+) -> DfxResult<NetworkDescriptor> {
+    let descriptor = resolve_configured_network(config, network_name)?;
+    let overridable = !descriptor.is_ic
+        && (descriptor.name == "local" || descriptor.r#type == NetworkType::Ephemeral);
+    if network_is_explicit || !overridable {
+        return Ok(descriptor);
+    }
+    match replica_url_override()? {
+        Some(url) => Ok(NetworkDescriptor {
+            providers: vec![url],
+            ..descriptor
+        }),
+        None => Ok(descriptor),
+    }
+}
+
+// always returns at least one url
+fn resolve_configured_network(
+    config: &ConfigInterface,
+    network_name: String,
+) -> DfxResult<NetworkDescriptor> {
     match config.get_network(&network_name) {
         Some(ConfigNetwork::ConfigNetworkProvider(network_provider)) => {
             let provider_urls = match &network_provider.providers {
@@ -96,6 +137,16 @@ pub fn get_network_descriptor<'a>(
     }
 }
 
+/// Return the replica URL set in the `DFX_REPLICA_URL` environment variable, if any.
+pub fn get_replica_url_override() -> DfxResult<Option<String>> {
+    match std::env::var("DFX_REPLICA_URL") {
+        Ok(url) if !url.is_empty() => parse_provider_url(&url)
+            .map(Some)
+            .context("Invalid URL in DFX_REPLICA_URL."),
+        _ => Ok(None),
+    }
+}
+
 pub fn create_agent_environment<'a>(
     env: &'a (dyn Environment + 'a),
     network: Option<String>,
@@ -140,4 +191,43 @@ mod tests {
             "http://127.0.0.1:8000"
         );
     }
+
+    fn resolve(config: &str, network_name: &str, network_is_explicit: bool) -> NetworkDescriptor {
+        let config = Config::from_str(config).unwrap();
+        resolve_network_descriptor(
+            config.get_config(),
+            network_name.to_string(),
+            network_is_explicit,
+            || Ok(Some("http://ci-replica:8000".to_string())),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn replica_url_overrides_the_default_local_network() {
+        let descriptor = resolve("{}", "local", false);
+        assert_eq!(descriptor.providers, vec!["http://ci-replica:8000"]);
+        assert_eq!(descriptor.r#type, NetworkType::Ephemeral);
+
+        let config = r#"{ "networks": { "dev": { "providers": ["http://dev:8000"], "type": "ephemeral" } } }"#;
+        let descriptor = resolve(config, "dev", false);
+        assert_eq!(descriptor.providers, vec!["http://ci-replica:8000"]);
+    }
+
+    #[test]
+    fn replica_url_does_not_override_an_explicit_network() {
+        let descriptor = resolve("{}", "local", true);
+        assert_eq!(descriptor.providers, vec!["http://127.0.0.1:8000"]);
+    }
+
+    #[test]
+    fn replica_url_does_not_override_persistent_networks() {
+        let descriptor = resolve(r#"{ "default_network": "ic" }"#, "ic", false);
+        assert_eq!(descriptor.providers, vec![DEFAULT_IC_GATEWAY]);
+        assert!(descriptor.is_ic);
+
+        let config = r#"{ "networks": { "staging": { "providers": ["https://staging.example"], "type": "persistent" } } }"#;
+        let descriptor = resolve(config, "staging", false);
+        assert_eq!(descriptor.providers, vec!["https://staging.example"]);
+    }
 }
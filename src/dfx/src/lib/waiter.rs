@@ -1,4 +1,6 @@
-use garcon::Delay;
+use garcon::{Delay, Waiter};
+use ic_agent::AgentError;
+use std::future::Future;
 use std::time::Duration;
 
 const RETRY_PAUSE: Duration = Duration::from_millis(200);
@@ -13,3 +15,124 @@ pub fn waiter_with_exponential_backoff() -> Delay {
 pub fn waiter_with_timeout(duration: Duration) -> Delay {
     Delay::builder().timeout(duration).build()
 }
+
+/// Whether retrying a request that failed with this error might succeed. Only errors reaching
+/// the replica (e.g. connection refused), timeouts and server errors (5xx) are transient.
+/// Anything else, like a 400 or a rejected call, fails the same way again and should not be
+/// retried.
+pub fn is_transient_error(err: &AgentError) -> bool {
+    match err {
+        AgentError::TimeoutWaitingForResponse() => true,
+        AgentError::HttpError(payload) => payload.status >= 500,
+        AgentError::TransportError(err) => match err.downcast_ref::<reqwest::Error>() {
+            Some(err) => {
+                err.is_connect()
+                    || err.is_timeout()
+                    || err.status().map_or(false, |s| s.is_server_error())
+            }
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Make `attempt` until it returns a value, pausing with `waiter` between attempts. An attempt
+/// returning `None`, e.g. a replica that is not healthy yet, or failing with a transient error
+/// is made again until the waiter times out. Any other error is returned right away.
+pub async fn retry_transient<T, F, Fut>(waiter: &mut Delay, mut attempt: F) -> Result<T, AgentError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<T>, AgentError>>,
+{
+    waiter.start();
+    loop {
+        let last_error = match attempt().await {
+            Ok(Some(value)) => return Ok(value),
+            Ok(None) => None,
+            Err(err) if is_transient_error(&err) => Some(err),
+            Err(err) => return Err(err),
+        };
+        if waiter.wait().is_err() {
+            return Err(last_error.unwrap_or_else(AgentError::TimeoutWaitingForResponse));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_agent::agent_error::HttpErrorPayload;
+
+    fn http_error(status: u16) -> AgentError {
+        AgentError::HttpError(HttpErrorPayload {
+            status,
+            content_type: None,
+            content: vec![],
+        })
+    }
+
+    #[test]
+    fn client_errors_are_not_transient() {
+        assert!(!is_transient_error(&http_error(400)));
+        assert!(!is_transient_error(&http_error(404)));
+    }
+
+    #[test]
+    fn server_errors_and_timeouts_are_transient() {
+        assert!(is_transient_error(&http_error(503)));
+        assert!(is_transient_error(&AgentError::TimeoutWaitingForResponse()));
+    }
+
+    fn retry(results: Vec<Result<Option<u32>, AgentError>>) -> (Result<u32, AgentError>, usize) {
+        let mut waiter = Delay::builder()
+            .throttle(Duration::from_millis(1))
+            .timeout(Duration::from_secs(10))
+            .build();
+        let mut results = results.into_iter();
+        let mut attempts = 0;
+        let result = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(retry_transient(&mut waiter, || {
+                attempts += 1;
+                let result = results.next().unwrap();
+                async move { result }
+            }));
+        (result, attempts)
+    }
+
+    #[test]
+    fn transient_errors_are_retried() {
+        let (result, attempts) = retry(vec![Err(http_error(503)), Ok(None), Ok(Some(42))]);
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn client_errors_fail_fast() {
+        let (result, attempts) = retry(vec![Err(http_error(400)), Ok(Some(42))]);
+        assert!(matches!(result, Err(AgentError::HttpError(payload)) if payload.status == 400));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn unknown_transport_errors_are_not_transient() {
+        let err = AgentError::TransportError(Box::new(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "unknown",
+        )));
+        assert!(!is_transient_error(&err));
+    }
+
+    #[test]
+    fn connection_refused_is_transient() {
+        // Nothing listens on a port that was just released.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let err = reqwest::blocking::get(&format!("http://127.0.0.1:{}/", port)).unwrap_err();
+        let err = AgentError::TransportError(Box::new(err));
+        assert!(is_transient_error(&err));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::error_invalid_data;
+use crate::lib::error::DfxResult;
+
+const WASM_HEADER_LEN: usize = 8;
+const CUSTOM_SECTION_ID: u8 = 0;
+
+/// Return a copy of a wasm module with the custom section `name` set to `payload`. Existing
+/// custom sections with the same name are removed, and the new one is appended at the end of
+/// the module.
+pub fn set_custom_section(wasm: &[u8], name: &str, payload: &[u8]) -> DfxResult<Vec<u8>> {
+    if wasm.len() < WASM_HEADER_LEN || &wasm[..4] != b"\0asm" {
+        return Err(error_invalid_data!("Not a wasm module."));
+    }
+
+    let mut result = wasm[..WASM_HEADER_LEN].to_vec();
+    let mut offset = WASM_HEADER_LEN;
+    while offset < wasm.len() {
+        let id = wasm[offset];
+        let (size, size_len) = read_u32_leb128(&wasm[offset + 1..])?;
+        let content_start = offset + 1 + size_len;
+        let content_end = content_start + size as usize;
+        if content_end > wasm.len() {
+            return Err(error_invalid_data!("Truncated wasm section."));
+        }
+        if !(id == CUSTOM_SECTION_ID
+            && custom_section_name(&wasm[content_start..content_end])? == name.as_bytes())
+        {
+            result.extend_from_slice(&wasm[offset..content_end]);
+        }
+        offset = content_end;
+    }
+
+    let mut content = vec![];
+    write_u32_leb128(&mut content, name.len() as u32);
+    content.extend_from_slice(name.as_bytes());
+    content.extend_from_slice(payload);
+
+    result.push(CUSTOM_SECTION_ID);
+    write_u32_leb128(&mut result, content.len() as u32);
+    result.extend_from_slice(&content);
+    Ok(result)
+}
+
+fn custom_section_name(content: &[u8]) -> DfxResult<&[u8]> {
+    let (len, len_len) = read_u32_leb128(content)?;
+    content
+        .get(len_len..len_len + len as usize)
+        .ok_or_else(|| error_invalid_data!("Truncated wasm custom section name."))
+}
+
+/// Read an unsigned LEB128 integer, returning it and the number of bytes it took.
+fn read_u32_leb128(bytes: &[u8]) -> DfxResult<(u32, usize)> {
+    let mut result: u32 = 0;
+    for (i, byte) in bytes.iter().take(5).enumerate() {
+        result |= u32::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((result, i + 1));
+        }
+    }
+    Err(error_invalid_data!(
+        "Invalid LEB128 integer in wasm module."
+    ))
+}
+
+fn write_u32_leb128(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    #[test]
+    fn custom_section_is_appended_and_replaced() {
+        let wasm = set_custom_section(EMPTY_MODULE, "dfx:sources", b"abc").unwrap();
+        let section = b"\0\x0f\x0bdfx:sourcesabc";
+        assert_eq!(wasm, [EMPTY_MODULE, section].concat());
+
+        let wasm = set_custom_section(&wasm, "other", b"").unwrap();
+        let wasm = set_custom_section(&wasm, "dfx:sources", b"abc").unwrap();
+        assert_eq!(wasm, [EMPTY_MODULE, b"\0\x06\x05other", section].concat());
+    }
+
+    #[test]
+    fn leb128_round_trip() {
+        for value in &[0, 1, 127, 128, 300, 16384, u32::MAX] {
+            let mut bytes = vec![];
+            write_u32_leb128(&mut bytes, *value);
+            assert_eq!(read_u32_leb128(&bytes).unwrap(), (*value, bytes.len()));
+        }
+    }
+
+    #[test]
+    fn rejects_non_wasm() {
+        assert!(set_custom_section(b"not wasm", "dfx:sources", b"").is_err());
+    }
+}
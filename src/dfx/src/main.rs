@@ -2,7 +2,7 @@ use crate::config::{dfx_version, dfx_version_str};
 use crate::lib::environment::{Environment, EnvironmentImpl};
 use crate::lib::logger::{create_root_logger, LoggingMode};
 
-use clap::{AppSettings, Clap};
+use clap::{AppSettings, Clap, ErrorKind};
 use semver::Version;
 use std::path::PathBuf;
 
@@ -31,6 +31,16 @@ pub struct CliOpts {
     #[clap(long)]
     identity: Option<String>,
 
+    /// Fail if dfx.json contains fields that dfx does not know about, e.g. a mistyped key.
+    /// This can also be enabled by setting "strict": true in dfx.json.
+    #[clap(long)]
+    strict_config: bool,
+
+    /// Fail instead of downloading anything, e.g. an SDK version that is not in the cache.
+    /// This can also be enabled by setting the DFX_OFFLINE environment variable.
+    #[clap(long)]
+    offline: bool,
+
     #[clap(subcommand)]
     command: commands::Command,
 }
@@ -100,9 +110,20 @@ fn setup_logging(opts: &CliOpts) -> (bool, slog::Logger) {
 }
 
 fn main() {
-    let cli_opts = CliOpts::parse();
+    let cli_opts = CliOpts::try_parse().unwrap_or_else(|err| match err.kind {
+        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => err.exit(),
+        _ => {
+            eprintln!("{}", err);
+            std::process::exit(lib::error::exit_code(&err.into()))
+        }
+    });
     let (progress_bar, log) = setup_logging(&cli_opts);
     let identity = cli_opts.identity;
+    let strict_config = cli_opts.strict_config;
+    if cli_opts.offline {
+        // Through the environment, this also applies to the dfx a project is redirected to.
+        std::env::set_var("DFX_OFFLINE", "1");
+    }
     let command = cli_opts.command;
     let result = match EnvironmentImpl::new() {
         Ok(env) => {
@@ -117,16 +138,23 @@ fn main() {
                         env.get_logger(),
                         "Trace mode enabled. Lots of logs coming up."
                     );
-                    commands::exec(&env, command)
+                    match env.get_config() {
+                        Some(config) if strict_config => config.check_unknown_fields(),
+                        _ => Ok(()),
+                    }
+                    .and_then(|_| commands::exec(&env, command))
                 }
                 Err(e) => Err(e),
             }
         }
         Err(e) => Err(e),
     };
+    // Errors exit with a code that depends on their category (see `lib::error::exit_code`):
+    // 2 for usage errors, 3 for build failures, 4 for network or replica errors and 70 for
+    // everything else.
     if let Err(err) = result {
         eprintln!("{}", err);
 
-        std::process::exit(255);
+        std::process::exit(lib::error::exit_code(&err));
     }
 }
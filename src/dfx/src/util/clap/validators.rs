@@ -1,6 +1,5 @@
 use crate::lib::nns_types::icpts::ICPTs;
 use humanize_rs::bytes::{Bytes, Unit};
-use std::str::FromStr;
 
 pub fn is_request_id(v: &str) -> Result<(), String> {
     // A valid Request Id starts with `0x` and is a series of 64 hexadecimals.
@@ -27,7 +26,71 @@ pub fn e8s_validator(e8s: &str) -> Result<(), String> {
 }
 
 pub fn icpts_amount_validator(icpts: &str) -> Result<(), String> {
-    ICPTs::from_str(icpts).map(|_| ())
+    parse_icpts(icpts)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+/// Why an amount of ICP could not be parsed.
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum IcptsParseError {
+    #[error("The amount of ICP is empty.")]
+    Empty,
+
+    #[error("Invalid amount of ICP '{0}': only digits, one decimal point and thousands separators are allowed.")]
+    InvalidDigit(String),
+
+    #[error("Invalid amount of ICP '{0}': thousands separators must separate groups of 3 digits.")]
+    InvalidSeparator(String),
+
+    #[error("Invalid amount of ICP '{0}': ICP can only be specified to the 8th decimal.")]
+    TooManyDecimals(String),
+
+    #[error("Invalid amount of ICP '{0}': the amount is too large.")]
+    Overflow(String),
+}
+
+/// Parses an amount of ICP with up to 8 decimals. Thousands separators ("1,000.5") and a
+/// trailing "ICP" ("100 ICP") are accepted for readability.
+pub fn parse_icpts(amount: &str) -> Result<ICPTs, IcptsParseError> {
+    let amount = amount.trim();
+    let amount = amount
+        .strip_suffix("ICP")
+        .or_else(|| amount.strip_suffix("icp"))
+        .map_or(amount, str::trim_end);
+    if amount.is_empty() {
+        return Err(IcptsParseError::Empty);
+    }
+
+    let (whole, fract) = match amount.find('.') {
+        Some(index) => (&amount[..index], &amount[index + 1..]),
+        None => (amount, ""),
+    };
+    if !whole.chars().all(|c| c.is_ascii_digit() || c == ',')
+        || !fract.chars().all(|c| c.is_ascii_digit())
+        || (whole.is_empty() && fract.is_empty())
+    {
+        return Err(IcptsParseError::InvalidDigit(amount.to_string()));
+    }
+    if whole.contains(',') {
+        let mut groups = whole.split(',');
+        let first = groups.next().unwrap_or_default();
+        if first.is_empty() || first.len() > 3 || groups.any(|group| group.len() != 3) {
+            return Err(IcptsParseError::InvalidSeparator(amount.to_string()));
+        }
+    }
+    if fract.len() > 8 {
+        return Err(IcptsParseError::TooManyDecimals(amount.to_string()));
+    }
+
+    let overflow = || IcptsParseError::Overflow(amount.to_string());
+    let icp = match whole.replace(',', "").as_str() {
+        "" => 0,
+        whole => whole.parse::<u64>().map_err(|_| overflow())?,
+    };
+    // Only digits are left, and at most 8 of them.
+    let e8s = format!("{:0<8}", fract).parse::<u64>().unwrap();
+    ICPTs::new(icp, e8s).map_err(|_| overflow())
 }
 
 pub fn memo_validator(memo: &str) -> Result<(), String> {
@@ -115,3 +178,89 @@ pub fn is_hsm_key_id(key_id: &str) -> Result<(), String> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn amount_accepts_thousands_separators_and_an_icp_suffix() {
+        assert_eq!(
+            parse_icpts("1,000.5").unwrap(),
+            ICPTs::new(1000, 50_000_000).unwrap()
+        );
+        assert_eq!(
+            parse_icpts("1,234,567").unwrap(),
+            ICPTs::new(1_234_567, 0).unwrap()
+        );
+        assert_eq!(parse_icpts("100 ICP").unwrap(), ICPTs::new(100, 0).unwrap());
+        assert_eq!(
+            parse_icpts("2.5icp").unwrap(),
+            ICPTs::new(2, 50_000_000).unwrap()
+        );
+        assert_eq!(
+            parse_icpts("100.012").unwrap(),
+            ICPTs::new(100, 1_200_000).unwrap()
+        );
+
+        assert!(parse_icpts("100.123456789").is_err());
+        assert!(parse_icpts("1,00").is_err());
+        assert!(parse_icpts(",100").is_err());
+        assert!(parse_icpts("1000,000").is_err());
+        assert!(parse_icpts("1.000,5").is_err());
+        assert!(parse_icpts("ICP").is_err());
+    }
+
+    #[test]
+    fn malformed_amounts_are_reported_with_their_cause() {
+        use IcptsParseError::*;
+
+        assert_eq!(parse_icpts(""), Err(Empty));
+        assert_eq!(parse_icpts(" ICP"), Err(Empty));
+        assert_eq!(parse_icpts("1.2.3"), Err(InvalidDigit("1.2.3".to_string())));
+        assert_eq!(parse_icpts("-1"), Err(InvalidDigit("-1".to_string())));
+        assert_eq!(parse_icpts("."), Err(InvalidDigit(".".to_string())));
+        assert_eq!(
+            parse_icpts("1,00"),
+            Err(InvalidSeparator("1,00".to_string()))
+        );
+        assert_eq!(
+            parse_icpts("0.123456789"),
+            Err(TooManyDecimals("0.123456789".to_string()))
+        );
+        assert_eq!(
+            parse_icpts("184467440738"),
+            Err(Overflow("184467440738".to_string()))
+        );
+        assert_eq!(
+            parse_icpts("99999999999999999999"),
+            Err(Overflow("99999999999999999999".to_string()))
+        );
+
+        assert_eq!(
+            parse_icpts(".5").unwrap(),
+            ICPTs::new(0, 50_000_000).unwrap()
+        );
+        assert_eq!(
+            parse_icpts("184467440737").unwrap(),
+            ICPTs::new(184467440737, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn displayed_edge_amounts_parse_back() {
+        for e8s in &[0, 1, 99_999_999, 100_000_000, 100_000_001, u64::MAX] {
+            let icpts = ICPTs::from_e8s(*e8s);
+            assert_eq!(parse_icpts(&icpts.to_string()), Ok(icpts));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn displayed_amounts_parse_back(e8s in any::<u64>()) {
+            let icpts = ICPTs::from_e8s(e8s);
+            prop_assert_eq!(parse_icpts(&icpts.to_string()), Ok(icpts));
+        }
+    }
+}
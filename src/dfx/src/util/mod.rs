@@ -1,3 +1,4 @@
+use crate::lib::candid::encode_parsed_args;
 use crate::lib::error::DfxResult;
 use crate::{error_invalid_argument, error_invalid_data, error_unknown};
 
@@ -141,8 +142,9 @@ pub fn blob_from_arguments(
                                 candid::pretty_parse::<IDLArgs>("Candid argument", &arguments)
                             }
                         });
-                        args.map_err(|e| error_invalid_argument!("Invalid Candid values: {}", e))?
-                            .to_bytes_with_types(&env, &func.args)
+                        let args = args
+                            .map_err(|e| error_invalid_argument!("Invalid Candid values: {}", e))?;
+                        return encode_parsed_args(&env, &func.args, &args);
                     } else if func.args.is_empty() {
                         use candid::Encode;
                         Encode!()